@@ -1,57 +1,407 @@
+use near_sdk::json_types::{Base58CryptoHash, U128};
+use near_sdk::store::IterableSet;
 use near_sdk::{
-    assert_one_yocto, env, ext_contract, near, require, AccountId, NearToken, Promise, PublicKey,
+    assert_one_yocto, borsh, env, ext_contract, near, require, AccountId, BorshStorageKey,
+    CryptoHash, Gas, NearToken, PanicOnDefault, Promise, PromiseError, PublicKey,
 };
 
+const GAS_KEY_CALLBACK: Gas = Gas::from_tgas(5);
+const STATE_KEY: &[u8] = b"STATE";
+const ERR_NOT_REGISTRY: &str = "Only the registry can perform this action";
+
 #[allow(dead_code)]
 #[ext_contract(ext_intents)]
 trait IntentsContract {
     fn add_public_key(public_key: PublicKey);
     fn remove_public_key(public_key: PublicKey);
+    fn ft_withdraw(
+        token: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: Option<String>,
+    ) -> U128;
+    fn mt_withdraw(
+        token: AccountId,
+        receiver_id: AccountId,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+        memo: Option<String>,
+        msg: Option<String>,
+    ) -> Vec<U128>;
+    fn mt_batch_transfer(
+        receiver_id: AccountId,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+    );
+}
+
+#[near]
+#[derive(BorshStorageKey)]
+enum Prefix {
+    AuthorizedKeys,
 }
 
-#[derive(Default)]
+#[derive(PanicOnDefault)]
 #[near(contract_state)]
-pub struct Contract {}
+pub struct Contract {
+    /// Solver registry that created the vault and manages it
+    registry_id: AccountId,
+    /// NEAR Intents contract holding the vault balances
+    intents_contract_id: AccountId,
+    /// Keys currently authorized for the vault account on NEAR Intents
+    authorized_keys: IterableSet<PublicKey>,
+    /// Whether at most one key can be authorized at a time
+    single_key_policy: bool,
+}
 
-/// TODO: the contract can be deployed as a global contract
+/// Pool accounts either hold a copy of the vault code or reference it as a global contract
+/// published by the solver registry.
 #[near]
 impl Contract {
+    /// Initialize the vault, called by the registry when it creates the pool account
+    #[init]
+    pub fn new(intents_contract_id: AccountId) -> Self {
+        Self {
+            registry_id: env::predecessor_account_id(),
+            intents_contract_id,
+            authorized_keys: IterableSet::new(Prefix::AuthorizedKeys),
+            single_key_policy: false,
+        }
+    }
+
+    /// Called by the registry after each vault upgrade. The state of up-to-date vaults is kept.
+    /// v0.1.0 vaults had no state: they were created as sub-accounts of the registry, and
+    /// start tracking `public_keys`, the keys the registry knows it authorized.
+    #[init(ignore_state)]
+    pub fn migrate(intents_contract_id: AccountId, public_keys: Vec<PublicKey>) -> Self {
+        let state = env::storage_read(STATE_KEY).unwrap_or_default();
+        if let Ok(contract) = borsh::from_slice::<Self>(&state) {
+            contract.require_registry();
+            return contract;
+        }
+
+        require!(state.is_empty(), "Unknown vault state");
+        let registry_id = legacy_parent_account_id(&env::current_account_id());
+        require!(
+            env::predecessor_account_id() == registry_id,
+            ERR_NOT_REGISTRY
+        );
+        let mut authorized_keys = IterableSet::new(Prefix::AuthorizedKeys);
+        authorized_keys.extend(public_keys);
+        Self {
+            registry_id,
+            intents_contract_id,
+            authorized_keys,
+            single_key_policy: false,
+        }
+    }
+
+    /// Authorize a key for the vault account on NEAR Intents. The key is recorded once
+    /// NEAR Intents has added it.
+    #[payable]
+    pub fn add_public_key(&mut self, public_key: PublicKey) -> Promise {
+        assert_one_yocto();
+        self.require_registry();
+        require!(
+            !self.single_key_policy || self.authorized_keys.is_empty(),
+            "Only one key can be authorized"
+        );
+        require!(
+            !self.authorized_keys.contains(&public_key),
+            "Key already authorized"
+        );
+
+        ext_intents::ext(self.intents_contract_id.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .add_public_key(public_key.clone())
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_KEY_CALLBACK)
+                    .on_public_key_added(public_key),
+            )
+    }
+
+    /// Revoke an authorized key. The key is dropped once NEAR Intents has removed it.
     #[payable]
-    pub fn add_public_key(
+    pub fn remove_public_key(&mut self, public_key: PublicKey) -> Promise {
+        assert_one_yocto();
+        self.require_registry();
+        require!(
+            self.authorized_keys.contains(&public_key),
+            "Key is not authorized"
+        );
+
+        ext_intents::ext(self.intents_contract_id.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .remove_public_key(public_key.clone())
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_KEY_CALLBACK)
+                    .on_public_key_removed(public_key),
+            )
+    }
+
+    /// Fails if the key couldn't be added, so that the registry sees the failure
+    #[private]
+    pub fn on_public_key_added(
+        &mut self,
+        public_key: PublicKey,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) {
+        require!(call_result.is_ok(), "Failed to add public key");
+        self.authorized_keys.insert(public_key);
+    }
+
+    /// Fails if the key couldn't be removed, so that the registry sees the failure
+    #[private]
+    pub fn on_public_key_removed(
         &mut self,
-        intents_contract_id: AccountId,
         public_key: PublicKey,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) {
+        require!(call_result.is_ok(), "Failed to remove public key");
+        self.authorized_keys.remove(&public_key);
+    }
+
+    /// Allow at most one authorized key at a time
+    #[payable]
+    pub fn set_single_key_policy(&mut self, enabled: bool) {
+        assert_one_yocto();
+        self.require_registry();
+        require!(
+            !enabled || self.authorized_keys.len() <= 1,
+            "More than one key is authorized"
+        );
+        self.single_key_policy = enabled;
+    }
+
+    /// Withdraw a NEP-141 token held by the vault on NEAR Intents to the receiver.
+    /// If `msg` is set, the token is transferred with `ft_transfer_call`.
+    #[payable]
+    pub fn ft_withdraw(
+        &mut self,
+        token: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: Option<String>,
     ) -> Promise {
         assert_one_yocto();
-        self.require_parent_account();
+        self.require_registry();
 
-        ext_intents::ext(intents_contract_id)
+        ext_intents::ext(self.intents_contract_id.clone())
             .with_attached_deposit(NearToken::from_yoctonear(1))
-            .add_public_key(public_key)
+            .ft_withdraw(token, receiver_id, amount, memo, msg)
     }
 
+    /// Withdraw NEP-245 tokens of the `token` contract held by the vault on NEAR Intents
+    /// to the receiver. If `msg` is set, the tokens are transferred with `mt_batch_transfer_call`.
     #[payable]
-    pub fn remove_public_key(
+    pub fn mt_withdraw(
         &mut self,
-        intents_contract_id: AccountId,
-        public_key: PublicKey,
+        token: AccountId,
+        receiver_id: AccountId,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+        memo: Option<String>,
+        msg: Option<String>,
+    ) -> Promise {
+        assert_one_yocto();
+        self.require_registry();
+
+        ext_intents::ext(self.intents_contract_id.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .mt_withdraw(token, receiver_id, token_ids, amounts, memo, msg)
+    }
+
+    /// Transfer token balances held by the vault to another account within NEAR Intents
+    #[payable]
+    pub fn transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+        memo: Option<String>,
     ) -> Promise {
         assert_one_yocto();
-        self.require_parent_account();
+        self.require_registry();
 
-        ext_intents::ext(intents_contract_id)
+        ext_intents::ext(self.intents_contract_id.clone())
             .with_attached_deposit(NearToken::from_yoctonear(1))
-            .remove_public_key(public_key)
+            .mt_batch_transfer(receiver_id, token_ids, amounts, None, memo)
+    }
+
+    /// Deploy new code to the vault. The code is passed as the raw input.
+    #[payable]
+    pub fn upgrade(&mut self) -> Promise {
+        assert_one_yocto();
+        self.require_registry();
+
+        let code = env::input().expect("Code not found");
+        Promise::new(env::current_account_id()).deploy_contract(code)
+    }
+
+    /// Switch the vault to the global contract published by the registry under `code_hash`.
+    /// Vaults referencing a global contract can't hold a copy of the code.
+    #[payable]
+    pub fn use_global_contract(&mut self, code_hash: Base58CryptoHash) -> Promise {
+        assert_one_yocto();
+        self.require_registry();
+
+        let code_hash: CryptoHash = code_hash.into();
+        Promise::new(env::current_account_id()).use_global_contract(code_hash.to_vec())
+    }
+
+    pub fn get_registry_id(&self) -> &AccountId {
+        &self.registry_id
+    }
+
+    pub fn get_intents_contract_id(&self) -> &AccountId {
+        &self.intents_contract_id
+    }
+
+    pub fn get_authorized_keys(&self) -> Vec<&PublicKey> {
+        self.authorized_keys.iter().collect()
+    }
+
+    pub fn get_single_key_policy(&self) -> bool {
+        self.single_key_policy
+    }
+
+    pub fn get_version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
     }
 }
 
 impl Contract {
-    fn require_parent_account(&self) {
-        let contract_id = env::current_account_id().to_string();
-        let (_, parent_account_id) = contract_id.split_once('.').expect("Invalid contract ID");
+    fn require_registry(&self) {
         require!(
-            env::predecessor_account_id() == parent_account_id,
-            "Only parent account can perform this action"
+            env::predecessor_account_id() == self.registry_id,
+            ERR_NOT_REGISTRY
         );
     }
 }
+
+/// Parent of a vault account created as a direct sub-account of the registry
+fn legacy_parent_account_id(account_id: &AccountId) -> AccountId {
+    account_id
+        .get_parent_account_id()
+        .expect("Invalid contract ID")
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    fn account(account_id: &str) -> AccountId {
+        account_id.parse().unwrap()
+    }
+
+    fn public_key() -> PublicKey {
+        "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+            .parse()
+            .unwrap()
+    }
+
+    fn set_context(current_account_id: &str, predecessor_account_id: &str) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(account(current_account_id))
+            .predecessor_account_id(account(predecessor_account_id))
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .build());
+    }
+
+    #[test]
+    fn test_new_stores_registry_and_intents() {
+        set_context("pool-0.registry.near", "registry.near");
+        let contract = Contract::new(account("intents.near"));
+        assert_eq!(contract.get_registry_id(), &account("registry.near"));
+        assert_eq!(contract.get_intents_contract_id(), &account("intents.near"));
+    }
+
+    #[test]
+    fn test_registry_manages_nested_vault() {
+        set_context("pool-0.vaults.registry.near", "registry.near");
+        let mut contract = Contract::new(account("intents.near"));
+        contract.set_single_key_policy(true);
+        assert!(contract.get_single_key_policy());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the registry can perform this action")]
+    fn test_parent_of_nested_vault_is_not_registry() {
+        set_context("pool-0.vaults.registry.near", "registry.near");
+        let mut contract = Contract::new(account("intents.near"));
+        set_context("pool-0.vaults.registry.near", "vaults.registry.near");
+        contract.set_single_key_policy(true);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the registry can perform this action")]
+    fn test_sub_account_of_registry_is_not_registry() {
+        set_context("pool-0.registry.near", "registry.near");
+        let mut contract = Contract::new(account("intents.near"));
+        set_context("pool-0.registry.near", "pool-1.registry.near");
+        contract.add_public_key(public_key());
+    }
+
+    #[test]
+    fn test_migrate_vault_without_key_tracking() {
+        set_context("pool-0.registry.near", "registry.near");
+        env::storage_write(STATE_KEY, &[]);
+        let contract = Contract::migrate(account("intents.near"), vec![public_key()]);
+        assert_eq!(contract.get_registry_id(), &account("registry.near"));
+        assert_eq!(contract.get_intents_contract_id(), &account("intents.near"));
+        assert_eq!(contract.get_authorized_keys(), vec![&public_key()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown vault state")]
+    fn test_migrate_unknown_state() {
+        set_context("pool-0.registry.near", "registry.near");
+        env::storage_write(STATE_KEY, &[1, 2, 3]);
+        Contract::migrate(account("intents.near"), vec![public_key()]);
+    }
+
+    #[test]
+    fn test_migrate_keeps_up_to_date_state() {
+        set_context("pool-0.vaults.registry.near", "registry.near");
+        env::state_write(&Contract::new(account("intents.near")));
+
+        let contract = Contract::migrate(account("other-intents.near"), vec![public_key()]);
+        assert_eq!(contract.get_registry_id(), &account("registry.near"));
+        assert_eq!(contract.get_intents_contract_id(), &account("intents.near"));
+        assert!(contract.get_authorized_keys().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the registry can perform this action")]
+    fn test_migrate_from_other_account() {
+        set_context("pool-0.registry.near", "other.near");
+        env::storage_write(STATE_KEY, &[]);
+        Contract::migrate(account("intents.near"), vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the registry can perform this action")]
+    fn test_migrate_nested_vault_without_registry() {
+        // A legacy vault is only managed by its direct parent account
+        set_context("pool-0.vaults.registry.near", "registry.near");
+        env::storage_write(STATE_KEY, &[]);
+        Contract::migrate(account("intents.near"), vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid contract ID")]
+    fn test_migrate_top_level_vault() {
+        set_context("vault", "registry.near");
+        env::storage_write(STATE_KEY, &[]);
+        Contract::migrate(account("intents.near"), vec![]);
+    }
+}
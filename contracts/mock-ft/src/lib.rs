@@ -10,7 +10,10 @@ use near_contract_standards::storage_management::{
 use near_sdk::borsh::BorshSerialize;
 use near_sdk::collections::LazyOption;
 use near_sdk::json_types::U128;
-use near_sdk::{log, near, AccountId, BorshStorageKey, NearToken, PanicOnDefault, PromiseOrValue};
+use near_sdk::{
+    assert_one_yocto, env, log, near, AccountId, BorshStorageKey, NearToken, PanicOnDefault,
+    Promise, PromiseOrValue,
+};
 
 #[derive(PanicOnDefault)]
 #[near(contract_state)]
@@ -70,6 +73,23 @@ impl Contract {
 
         this
     }
+
+    /// Wrap the attached NEAR, as wNEAR does
+    #[payable]
+    pub fn near_deposit(&mut self) {
+        let account_id = env::predecessor_account_id();
+        let amount = env::attached_deposit().as_yoctonear();
+        self.token.internal_deposit(&account_id, amount);
+    }
+
+    /// Unwrap the given amount back to NEAR, as wNEAR does
+    #[payable]
+    pub fn near_withdraw(&mut self, amount: U128) -> Promise {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        self.token.internal_withdraw(&account_id, amount.0);
+        Promise::new(account_id).transfer(NearToken::from_yoctonear(amount.0))
+    }
 }
 
 #[near]
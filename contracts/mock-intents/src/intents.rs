@@ -0,0 +1,103 @@
+use near_sdk::{log, near, require, AccountId, CurveType, PublicKey};
+use std::collections::BTreeMap;
+
+use crate::*;
+
+const ED25519_PREFIX: &str = "ed25519:";
+
+/// Payload signed with one of the signer's public keys. Unlike NEAR Intents, there are no
+/// nonces or deadlines, so a signed payload can be replayed.
+#[near(serializers = [json])]
+pub struct IntentPayload {
+    pub signer_id: AccountId,
+    pub intents: Vec<Intent>,
+}
+
+#[near(serializers = [json])]
+#[serde(tag = "intent", rename_all = "snake_case")]
+pub enum Intent {
+    /// Changes of the signer's token balances, e.g. `{"nep141:usdc.near": "-1000"}`
+    TokenDiff { diff: BTreeMap<String, String> },
+}
+
+#[near(serializers = [json])]
+pub struct SignedPayload {
+    /// JSON of the `IntentPayload`, as signed
+    pub payload: String,
+    pub public_key: PublicKey,
+    /// `ed25519:<base58>` signature of the payload
+    pub signature: String,
+}
+
+#[near]
+impl Contract {
+    /// Execute intents signed by their signers. The token diffs of all intents have to add
+    /// up to zero for each token, and are applied all or nothing.
+    pub fn execute_intents(&mut self, signed: Vec<SignedPayload>) {
+        let mut diffs: Vec<(AccountId, String, i128)> = vec![];
+        for signed_payload in signed {
+            let payload = self.verify_payload(&signed_payload);
+            for intent in payload.intents {
+                match intent {
+                    Intent::TokenDiff { diff } => {
+                        for (token_id, amount) in diff {
+                            let amount = amount.parse::<i128>().expect("Invalid token diff");
+                            diffs.push((payload.signer_id.clone(), token_id, amount));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut totals: BTreeMap<&str, i128> = BTreeMap::new();
+        for (_, token_id, amount) in &diffs {
+            *totals.entry(token_id.as_str()).or_default() += amount;
+        }
+        require!(
+            totals.values().all(|total| *total == 0),
+            "Token diffs don't add up to zero"
+        );
+
+        // Debit before crediting, so that every signer covers its own diff
+        diffs.sort_by_key(|(_, _, amount)| *amount >= 0);
+        for (account_id, token_id, amount) in diffs {
+            if amount < 0 {
+                self.internal_withdraw(&account_id, &token_id, amount.unsigned_abs());
+            } else {
+                self.internal_deposit(&account_id, &token_id, amount.unsigned_abs());
+            }
+            log!("Token diff {} {} for {}", amount, token_id, account_id);
+        }
+    }
+}
+
+impl Contract {
+    fn verify_payload(&self, signed_payload: &SignedPayload) -> IntentPayload {
+        let payload: IntentPayload =
+            serde_json::from_str(&signed_payload.payload).expect("Invalid payload");
+        let public_key = &signed_payload.public_key;
+        require!(
+            public_key.curve_type() == CurveType::ED25519,
+            "Only ed25519 keys are supported"
+        );
+        require!(
+            self.internal_get_account(&payload.signer_id)
+                .contains(public_key),
+            "Public key not registered for signer"
+        );
+
+        let signature: [u8; 64] = signed_payload
+            .signature
+            .strip_prefix(ED25519_PREFIX)
+            .and_then(|signature| bs58::decode(signature).into_vec().ok())
+            .and_then(|signature| signature.try_into().ok())
+            .expect("Invalid signature");
+        let public_key: [u8; 32] = public_key.as_bytes()[1..].try_into().unwrap();
+        require!(
+            env::ed25519_verify(&signature, signed_payload.payload.as_bytes(), &public_key),
+            "Invalid signature"
+        );
+
+        payload
+    }
+}
@@ -1,21 +1,45 @@
 use near_sdk::store::LookupMap;
 use near_sdk::{
-    assert_one_yocto, env, near, AccountId, BorshStorageKey, PanicOnDefault, PublicKey,
+    assert_one_yocto, env, near, require, AccountId, BorshStorageKey, PanicOnDefault, PublicKey,
 };
 use std::collections::HashSet;
 
+mod intents;
+mod multi_token;
 mod token_receiver;
+mod withdraw;
 
 #[derive(PanicOnDefault)]
 #[near(contract_state)]
 pub struct Contract {
     public_keys: LookupMap<AccountId, HashSet<PublicKey>>,
+    /// Token balances keyed by account and multi-token ID, e.g. `nep141:<token>`
+    balances: LookupMap<(AccountId, String), u128>,
+    failures: FailureConfig,
+}
+
+/// Calls that fail on demand, so that tests can cover the error handling of callers
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Default)]
+#[serde(default)]
+pub struct FailureConfig {
+    /// Panic in `add_public_key`
+    pub add_public_key: bool,
+    /// Panic in `remove_public_key`
+    pub remove_public_key: bool,
+    /// Part of each `ft_on_transfer` amount refunded to the sender, in basis points
+    pub ft_refund_bps: u32,
+    /// Only refund transfers of this token. `None` refunds all tokens.
+    pub ft_refund_token_id: Option<AccountId>,
+    /// Run out of gas in `ft_on_transfer`
+    pub burn_gas: bool,
 }
 
 #[near]
 #[derive(BorshStorageKey)]
 pub enum Prefix {
     PublicKeys,
+    Balances,
 }
 
 #[near]
@@ -25,12 +49,24 @@ impl Contract {
     pub fn new() -> Self {
         Self {
             public_keys: LookupMap::new(Prefix::PublicKeys),
+            balances: LookupMap::new(Prefix::Balances),
+            failures: FailureConfig::default(),
         }
     }
 
+    #[private]
+    pub fn set_failures(&mut self, failures: FailureConfig) {
+        self.failures = failures;
+    }
+
+    pub fn get_failures(&self) -> FailureConfig {
+        self.failures.clone()
+    }
+
     #[payable]
     pub fn add_public_key(&mut self, public_key: PublicKey) {
         assert_one_yocto();
+        require!(!self.failures.add_public_key, "Failed to add public key");
 
         let account_id = env::predecessor_account_id();
         let mut keys = self.internal_get_account(&account_id);
@@ -41,6 +77,10 @@ impl Contract {
     #[payable]
     pub fn remove_public_key(&mut self, public_key: PublicKey) {
         assert_one_yocto();
+        require!(
+            !self.failures.remove_public_key,
+            "Failed to remove public key"
+        );
         let account_id = env::predecessor_account_id();
         let mut keys = self.internal_get_account(&account_id);
         keys.remove(&public_key);
@@ -53,6 +93,26 @@ impl Contract {
 }
 
 impl Contract {
+    fn internal_balance_of(&self, account_id: &AccountId, token_id: &str) -> u128 {
+        self.balances
+            .get(&(account_id.clone(), token_id.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn internal_deposit(&mut self, account_id: &AccountId, token_id: &str, amount: u128) {
+        let balance = self.internal_balance_of(account_id, token_id) + amount;
+        self.balances
+            .insert((account_id.clone(), token_id.to_string()), balance);
+    }
+
+    fn internal_withdraw(&mut self, account_id: &AccountId, token_id: &str, amount: u128) {
+        let balance = self.internal_balance_of(account_id, token_id);
+        require!(balance >= amount, "Insufficient balance");
+        self.balances
+            .insert((account_id.clone(), token_id.to_string()), balance - amount);
+    }
+
     fn internal_get_account(&self, account_id: &AccountId) -> HashSet<PublicKey> {
         self.public_keys
             .get(account_id)
@@ -0,0 +1,155 @@
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, ext_contract, log, near, AccountId, Gas, PromiseOrValue, PromiseResult,
+};
+
+use crate::*;
+
+const GAS_MT_RESOLVE_TRANSFER: Gas = Gas::from_tgas(10);
+
+#[allow(dead_code)]
+#[ext_contract(ext_mt_receiver)]
+trait MultiTokenReceiver {
+    fn mt_on_transfer(
+        sender_id: AccountId,
+        previous_owner_ids: Vec<AccountId>,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>>;
+}
+
+#[near]
+impl Contract {
+    #[payable]
+    #[allow(unused_variables)]
+    pub fn mt_batch_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        for (token_id, amount) in token_ids.iter().zip(&amounts) {
+            self.internal_withdraw(&sender_id, token_id, amount.0);
+            self.internal_deposit(&receiver_id, token_id, amount.0);
+            log!(
+                "Transfer {} {} from {} to {}",
+                amount.0,
+                token_id,
+                sender_id,
+                receiver_id
+            );
+        }
+    }
+
+    #[payable]
+    pub fn mt_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: String,
+        amount: U128,
+        approval: Option<(AccountId, u64)>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        self.mt_batch_transfer_call(
+            receiver_id,
+            vec![token_id],
+            vec![amount],
+            Some(vec![approval]),
+            memo,
+            msg,
+        )
+    }
+
+    #[payable]
+    pub fn mt_batch_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        let sender_id = env::predecessor_account_id();
+        self.mt_batch_transfer(
+            receiver_id.clone(),
+            token_ids.clone(),
+            amounts.clone(),
+            approvals,
+            memo,
+        );
+
+        ext_mt_receiver::ext(receiver_id.clone())
+            .mt_on_transfer(
+                sender_id.clone(),
+                vec![sender_id.clone(); token_ids.len()],
+                token_ids.clone(),
+                amounts.clone(),
+                msg,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_MT_RESOLVE_TRANSFER)
+                    .mt_resolve_transfer(sender_id, receiver_id, token_ids, amounts),
+            )
+            .into()
+    }
+
+    pub fn mt_balance_of(&self, account_id: AccountId, token_id: String) -> U128 {
+        U128(self.internal_balance_of(&account_id, &token_id))
+    }
+
+    pub fn mt_batch_balance_of(&self, account_id: AccountId, token_ids: Vec<String>) -> Vec<U128> {
+        token_ids
+            .iter()
+            .map(|token_id| U128(self.internal_balance_of(&account_id, token_id)))
+            .collect()
+    }
+
+    /// Returns the used amounts. The refunds are returned to the sender, as far as the
+    /// receiver still holds them.
+    #[private]
+    pub fn mt_resolve_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+    ) -> Vec<U128> {
+        let refunds = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                serde_json::from_slice::<Vec<U128>>(&value).unwrap_or_else(|_| amounts.clone())
+            }
+            PromiseResult::Failed => amounts.clone(),
+        };
+
+        token_ids
+            .iter()
+            .zip(amounts.iter().zip(&refunds))
+            .map(|(token_id, (amount, refund))| {
+                let refund = refund
+                    .0
+                    .min(amount.0)
+                    .min(self.internal_balance_of(&receiver_id, token_id));
+                if refund > 0 {
+                    self.internal_withdraw(&receiver_id, token_id, refund);
+                    self.internal_deposit(&sender_id, token_id, refund);
+                    log!(
+                        "Refund {} {} from {} to {}",
+                        refund,
+                        token_id,
+                        receiver_id,
+                        sender_id
+                    );
+                }
+                U128(amount.0 - refund)
+            })
+            .collect()
+    }
+}
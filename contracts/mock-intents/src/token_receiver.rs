@@ -17,6 +17,12 @@ impl Contract {
             msg.parse().unwrap()
         };
 
+        if self.failures.burn_gas {
+            loop {
+                env::sha256(b"burn gas");
+            }
+        }
+
         let token_id = env::predecessor_account_id();
 
         log!(
@@ -26,6 +32,15 @@ impl Contract {
             receiver_id
         );
 
-        PromiseOrValue::Value(U128(0))
+        let refund = match &self.failures.ft_refund_token_id {
+            Some(refund_token_id) if *refund_token_id != token_id => 0,
+            _ => amount.0 * u128::from(self.failures.ft_refund_bps.min(10_000)) / 10_000,
+        };
+        self.internal_deposit(
+            &receiver_id,
+            &format!("nep141:{}", token_id),
+            amount.0 - refund,
+        );
+        PromiseOrValue::Value(U128(refund))
     }
 }
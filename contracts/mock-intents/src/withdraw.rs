@@ -0,0 +1,62 @@
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, ext_contract, log, near, AccountId, Gas, NearToken, Promise, PromiseError,
+};
+
+use crate::*;
+
+const GAS_FT_TRANSFER: Gas = Gas::from_tgas(10);
+const GAS_FT_RESOLVE_WITHDRAW: Gas = Gas::from_tgas(5);
+
+#[allow(dead_code)]
+#[ext_contract(ext_ft)]
+trait FungibleToken {
+    fn ft_transfer(receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+#[near]
+impl Contract {
+    /// Withdraw a NEP-141 token to the receiver
+    #[payable]
+    #[allow(unused_variables)]
+    pub fn ft_withdraw(
+        &mut self,
+        token: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: Option<String>,
+    ) -> Promise {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        self.internal_withdraw(&sender_id, &format!("nep141:{}", token), amount.0);
+
+        ext_ft::ext(token.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_FT_TRANSFER)
+            .ft_transfer(receiver_id, amount, memo)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FT_RESOLVE_WITHDRAW)
+                    .ft_resolve_withdraw(token, sender_id, amount),
+            )
+    }
+
+    /// Returns the withdrawn amount. The balance is restored if the transfer failed.
+    #[private]
+    pub fn ft_resolve_withdraw(
+        &mut self,
+        token: AccountId,
+        sender_id: AccountId,
+        amount: U128,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) -> U128 {
+        if call_result.is_ok() {
+            return amount;
+        }
+
+        log!("Withdrawal of {} {} failed", amount.0, token);
+        self.internal_deposit(&sender_id, &format!("nep141:{}", token), amount.0);
+        U128(0)
+    }
+}
@@ -1,17 +1,47 @@
 use crate::*;
+use near_sdk::json_types::U128;
 use near_sdk::near;
 
 #[near]
 impl Contract {
-    /// Approve a docker compose hash for worker registration
-    pub fn approve_compose_hash(&mut self, compose_hash: String) {
+    /// Approve a docker compose hash for worker registration.
+    /// Approving an already approved hash overwrites its metadata.
+    pub fn approve_compose_hash(
+        &mut self,
+        compose_hash: String,
+        label: Option<String>,
+        source_repo: Option<String>,
+        source_commit: Option<String>,
+        expires_at_ms: Option<TimestampMs>,
+    ) {
         self.assert_owner();
         DockerComposeHash::try_from_hex(compose_hash.clone()).expect("Invalid compose hash");
+        if let Some(expires_at_ms) = expires_at_ms {
+            require!(
+                expires_at_ms > block_timestamp_ms(),
+                "Expiry must be in the future"
+            );
+        }
 
-        self.approved_compose_hashes.insert(compose_hash.clone());
+        let info = ComposeHashInfo {
+            compose_hash: compose_hash.clone(),
+            label,
+            source_repo,
+            source_commit,
+            approved_by: env::predecessor_account_id(),
+            approved_at_ms: block_timestamp_ms(),
+            expires_at_ms,
+        };
+        self.approved_compose_hashes
+            .insert(compose_hash.clone(), info.clone());
 
         Event::ComposeHashApproved {
             compose_hash: &compose_hash,
+            label: &info.label,
+            source_repo: &info.source_repo,
+            source_commit: &info.source_commit,
+            approved_by: &info.approved_by,
+            expires_at_ms: &info.expires_at_ms,
         }
         .emit();
     }
@@ -22,7 +52,7 @@ impl Contract {
         DockerComposeHash::try_from_hex(compose_hash.clone()).expect("Invalid compose hash");
 
         require!(
-            self.approved_compose_hashes.remove(&compose_hash),
+            self.approved_compose_hashes.remove(&compose_hash).is_some(),
             "Compose hash not found"
         );
 
@@ -32,6 +62,117 @@ impl Contract {
         .emit();
     }
 
+    /// Set who can create liquidity pools
+    pub fn set_pool_creation_policy(&mut self, policy: PoolCreationPolicy) {
+        self.assert_owner();
+        self.pool_creation_policy = policy;
+
+        Event::PoolCreationPolicyChanged { policy: &policy }.emit();
+    }
+
+    /// Whitelist an account to create pools under the `Whitelisted` policy
+    pub fn add_pool_creator(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        require!(
+            self.pool_creators.insert(account_id.clone()),
+            "Pool creator already whitelisted"
+        );
+
+        Event::PoolCreatorAdded {
+            account_id: &account_id,
+        }
+        .emit();
+    }
+
+    pub fn remove_pool_creator(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        require!(
+            self.pool_creators.remove(&account_id),
+            "Pool creator not found"
+        );
+
+        Event::PoolCreatorRemoved {
+            account_id: &account_id,
+        }
+        .emit();
+    }
+
+    /// Allow a NEP-141 token in new pools, or update its configuration
+    pub fn allow_token(&mut self, token_id: AccountId, decimals: u8, min_deposit: U128) {
+        self.assert_owner();
+        self.allowed_tokens.insert(
+            token_id.clone(),
+            TokenConfig {
+                decimals,
+                min_deposit,
+            },
+        );
+
+        Event::TokenAllowed {
+            token_id: &token_id,
+            decimals: &decimals,
+            min_deposit: &min_deposit,
+        }
+        .emit();
+    }
+
+    /// Disallow a token in new pools. Existing pools with the token are not affected.
+    pub fn remove_token(&mut self, token_id: AccountId) {
+        self.assert_owner();
+        require!(
+            self.allowed_tokens.remove(&token_id).is_some(),
+            "Token not found"
+        );
+
+        Event::TokenRemoved {
+            token_id: &token_id,
+        }
+        .emit();
+    }
+
+    /// Set the minimum time between scheduling and applying a pool fee change
+    pub fn set_pool_fee_delay_ms(&mut self, pool_fee_delay_ms: TimestampMs) {
+        self.assert_owner();
+        self.pool_fee_delay_ms = pool_fee_delay_ms;
+
+        Event::PoolFeeDelayUpdated {
+            pool_fee_delay_ms: &pool_fee_delay_ms,
+        }
+        .emit();
+    }
+
+    /// Set the time after which a pending batch deposit can be reclaimed
+    pub fn set_pending_deposit_timeout_ms(&mut self, pending_deposit_timeout_ms: TimestampMs) {
+        self.assert_owner();
+        self.pending_deposit_timeout_ms = pending_deposit_timeout_ms;
+
+        Event::PendingDepositTimeoutUpdated {
+            pending_deposit_timeout_ms: &pending_deposit_timeout_ms,
+        }
+        .emit();
+    }
+
+    /// Set the wNEAR contract used to wrap native NEAR deposits
+    pub fn set_wnear_contract_id(&mut self, wnear_contract_id: Option<AccountId>) {
+        self.assert_owner();
+        Event::WnearContractUpdated {
+            wnear_contract_id: &wnear_contract_id,
+        }
+        .emit();
+        self.wnear_contract_id = wnear_contract_id;
+    }
+
+    /// Log every worker ping, or only pings of workers that become active again
+    pub fn set_ping_events(&mut self, ping_events: PingEvents) {
+        self.assert_owner();
+        self.ping_events = ping_events;
+
+        Event::PingEventsUpdated {
+            ping_events: &ping_events,
+        }
+        .emit();
+    }
+
     pub fn change_owner(&mut self, new_owner_id: AccountId) {
         self.assert_owner();
         let old_owner_id = self.owner_id.clone();
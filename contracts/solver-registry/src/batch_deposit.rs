@@ -0,0 +1,253 @@
+use near_sdk::json_types::U128;
+use near_sdk::{near, require, AccountId, Gas, NearToken, PromiseOrValue, PromiseResult};
+
+use crate::events::Event;
+use crate::ext::ext_ft;
+use crate::math::mul_div;
+use crate::pool::{ERR_BAD_TOKEN_ID, ERR_INVALID_AMOUNT, ERR_POOL_NOT_FOUND};
+use crate::*;
+
+/// Default time after which a pending batch deposit can be reclaimed
+pub(crate) const DEFAULT_PENDING_DEPOSIT_TIMEOUT_MS: TimestampMs = 60 * 60 * 1000; // 1 hour
+const GAS_BATCH_DEPOSIT_CALLBACK: Gas = Gas::from_tgas(30);
+const GAS_PENDING_DEPOSIT_REFUND_CALLBACK: Gas = Gas::from_tgas(10);
+
+#[near]
+impl Contract {
+    /// Reclaim the tokens held for a batch deposit, once the pending deposit has timed out
+    /// or the pool no longer accepts deposits.
+    pub fn reclaim_pending_deposit(&mut self, pool_id: u32) {
+        let sender_id = env::predecessor_account_id();
+        let key = (pool_id, sender_id.clone());
+        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        let pending = self
+            .pending_deposits
+            .remove(&key)
+            .expect("No pending deposit");
+        require!(
+            pool.status != PoolStatus::Active
+                || block_timestamp_ms() >= pending.created_at_ms + self.pending_deposit_timeout_ms,
+            "Pending deposit has not timed out yet"
+        );
+
+        for (token_index, (token_id, amount)) in
+            pool.token_ids.iter().zip(&pending.amounts).enumerate()
+        {
+            if amount.0 > 0 {
+                self.refund_pending_deposit(pool_id, &sender_id, token_id, token_index, *amount);
+            }
+        }
+
+        Event::PendingDepositReclaimed {
+            pool_id: &pool_id,
+            sender_id: &sender_id,
+            amounts: &pending.amounts,
+        }
+        .emit();
+    }
+
+    /// `deposit` was received from the sender, of which `accepted` was forwarded to the pool
+    /// for `shares`, by token. The token at `token_index` completed the batch, its refund is
+    /// returned to `ft_on_transfer`.
+    #[private]
+    pub fn on_batch_deposit_into_pool(
+        &mut self,
+        pool_id: u32,
+        sender_id: AccountId,
+        token_index: u32,
+        deposit: Vec<U128>,
+        accepted: Vec<U128>,
+        shares: Vec<U128>,
+    ) -> U128 {
+        // ft_transfer_call() returns the used fund
+        let used: Vec<Balance> = accepted
+            .iter()
+            .enumerate()
+            .map(|(i, accepted)| match env::promise_result(i as u64) {
+                PromiseResult::Successful(value) => {
+                    serde_json::from_slice::<U128>(&value).map_or(0, |used| used.0.min(accepted.0))
+                }
+                PromiseResult::Failed => 0,
+            })
+            .collect();
+        // The used amounts can't be returned, so each is credited at the pool ratio even if
+        // another token of the batch was refunded
+        let reserved: Balance = shares.iter().map(|shares| shares.0).sum();
+        let minted: Balance = used
+            .iter()
+            .zip(&accepted)
+            .zip(&shares)
+            .map(|((used, accepted), shares)| mul_div(shares.0, *used, accepted.0))
+            .sum();
+
+        let pool = self.pools.get_mut(pool_id).expect(ERR_POOL_NOT_FOUND);
+        for (i, (used, accepted)) in used.iter().zip(&accepted).enumerate() {
+            pool.release_deposit(&sender_id, i, accepted.0 - used);
+        }
+        pool.release_shares(reserved - minted);
+        if minted > 0 {
+            pool.mint_shares(&sender_id, minted);
+        }
+        let token_ids = pool.token_ids.clone();
+        self.pools.flush();
+
+        let refunds: Vec<U128> = deposit
+            .iter()
+            .zip(&used)
+            .map(|(amount, used)| U128(amount.0 - used))
+            .collect();
+        // The held tokens are refunded by transfer, the last one through `ft_on_transfer`
+        for (i, token_id) in token_ids.iter().enumerate() {
+            if i != token_index as usize && refunds[i].0 > 0 {
+                self.refund_pending_deposit(pool_id, &sender_id, token_id, i, refunds[i]);
+            }
+        }
+
+        Event::BatchDepositIntoPool {
+            pool_id: &pool_id,
+            sender_id: &sender_id,
+            token_ids: &token_ids,
+            amounts: &deposit,
+            refunds: &refunds,
+            shares: &U128(minted),
+        }
+        .emit();
+
+        refunds[token_index as usize]
+    }
+
+    /// Hold the refund again if it couldn't be transferred, so that it can be reclaimed
+    #[private]
+    pub fn on_pending_deposit_refunded(
+        &mut self,
+        pool_id: u32,
+        sender_id: AccountId,
+        token_index: u32,
+        amount: U128,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) {
+        if call_result.is_err() {
+            self.hold_deposit(pool_id, &sender_id, token_index as usize, amount.0);
+        }
+    }
+}
+
+impl Contract {
+    /// Hold the transferred token until all pool tokens have arrived, then deposit them together
+    pub(crate) fn batch_deposit_into_pool(
+        &mut self,
+        pool_id: u32,
+        token_id: &AccountId,
+        sender_id: &AccountId,
+        amount: Balance,
+    ) -> PromiseOrValue<U128> {
+        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        pool.assert_active();
+        require!(pool.token_ids.contains(token_id), ERR_BAD_TOKEN_ID);
+        require!(amount > 0, ERR_INVALID_AMOUNT);
+        let token_index = pool.token_index(token_id);
+
+        let key = (pool_id, sender_id.clone());
+        let mut deposit: Vec<Balance> = self.pending_deposits.get(&key).map_or_else(
+            || vec![0; pool.token_ids.len()],
+            |pending| pending.amounts.iter().map(|amount| amount.0).collect(),
+        );
+        deposit[token_index] += amount;
+
+        if deposit.contains(&0) {
+            self.hold_deposit(pool_id, sender_id, token_index, amount);
+            Event::DepositHeld {
+                pool_id: &pool_id,
+                sender_id,
+                token_id,
+                amount: &U128(amount),
+            }
+            .emit();
+            return PromiseOrValue::Value(U128(0));
+        }
+
+        self.pending_deposits.remove(&key);
+        let (accepted, shares) = self.reserve_deposit(pool_id, sender_id, &deposit);
+
+        // deposit all tokens into NEAR Intents
+        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        let pool_account_id = self.get_pool_account_id(pool_id);
+        let deposits = pool
+            .token_ids
+            .iter()
+            .zip(&accepted)
+            .map(|(token_id, amount)| {
+                ext_ft::ext(token_id.clone())
+                    .with_attached_deposit(NearToken::from_yoctonear(1))
+                    .ft_transfer_call(
+                        self.intents_contract_id.clone(),
+                        U128(*amount),
+                        Some("deposit into pool".to_string()),
+                        pool_account_id.to_string(),
+                    )
+            })
+            .reduce(|deposits, deposit| deposits.and(deposit))
+            .expect("Pool has no tokens");
+
+        deposits
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_BATCH_DEPOSIT_CALLBACK)
+                    .on_batch_deposit_into_pool(
+                        pool_id,
+                        sender_id.clone(),
+                        token_index as u32,
+                        deposit.into_iter().map(U128).collect(),
+                        accepted.into_iter().map(U128).collect(),
+                        shares.into_iter().map(U128).collect(),
+                    ),
+            )
+            .into()
+    }
+
+    fn hold_deposit(
+        &mut self,
+        pool_id: u32,
+        sender_id: &AccountId,
+        token_index: usize,
+        amount: Balance,
+    ) {
+        let token_count = self
+            .pools
+            .get(pool_id)
+            .expect(ERR_POOL_NOT_FOUND)
+            .token_ids
+            .len();
+        let pending = self
+            .pending_deposits
+            .entry((pool_id, sender_id.clone()))
+            .or_insert_with(|| PendingDeposit {
+                amounts: vec![U128(0); token_count],
+                created_at_ms: block_timestamp_ms(),
+            });
+        pending.amounts[token_index].0 += amount;
+    }
+
+    fn refund_pending_deposit(
+        &self,
+        pool_id: u32,
+        sender_id: &AccountId,
+        token_id: &AccountId,
+        token_index: usize,
+        amount: U128,
+    ) {
+        ext_ft::ext(token_id.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .ft_transfer(sender_id.clone(), amount, None)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_PENDING_DEPOSIT_REFUND_CALLBACK)
+                    .on_pending_deposit_refunded(
+                        pool_id,
+                        sender_id.clone(),
+                        token_index as u32,
+                        amount,
+                    ),
+            );
+    }
+}
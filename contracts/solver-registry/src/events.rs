@@ -1,11 +1,13 @@
+use near_sdk::json_types::U128;
 use near_sdk::serde::Serialize;
-use near_sdk::serde_json::json;
-use near_sdk::{log, AccountId, PublicKey};
+use near_sdk::serde_json::{json, Value};
+use near_sdk::{log, AccountId, NearToken, PublicKey};
 
-use crate::types::TimestampMs;
+use crate::pool::{DepositMode, PoolCreationPolicy, PoolStatus, TokenLimits};
+use crate::types::{PingEvents, TimestampMs};
 
 pub const EVENT_STANDARD: &str = "solver-registry";
-pub const EVENT_STANDARD_VERSION: &str = "1.0.0";
+pub const EVENT_STANDARD_VERSION: &str = "1.2.0";
 
 #[derive(Serialize)]
 #[serde(
@@ -23,6 +25,12 @@ pub enum Event<'a> {
         compose_hash: &'a String,
         checksum: &'a String,
     },
+    WorkerRegistrationFailed {
+        worker_id: &'a AccountId,
+        pool_id: &'a u32,
+        public_key: &'a PublicKey,
+        reason: &'a str,
+    },
     WorkerRemoved {
         worker_id: &'a AccountId,
         pool_id: &'a u32,
@@ -30,22 +38,179 @@ pub enum Event<'a> {
         compose_hash: &'a String,
         checksum: &'a String,
     },
+    PoolKeysReconciled {
+        pool_id: &'a u32,
+        removed_keys: &'a Vec<PublicKey>,
+        failed_keys: &'a Vec<PublicKey>,
+        removed_worker_id: &'a Option<AccountId>,
+    },
     CreateLiquidityPool {
         pool_id: &'a u32,
         token_ids: &'a Vec<AccountId>,
         fee: &'a u32,
+        creator_id: &'a AccountId,
+    },
+    CreateLiquidityPoolFailed {
+        pool_id: &'a u32,
+        creator_id: &'a AccountId,
+        refund: &'a NearToken,
+    },
+    DepositIntoPool {
+        pool_id: &'a u32,
+        sender_id: &'a AccountId,
+        token_id: &'a AccountId,
+        amount: &'a U128,
+        refund: &'a U128,
+        shares: &'a U128,
+    },
+    DepositHeld {
+        pool_id: &'a u32,
+        sender_id: &'a AccountId,
+        token_id: &'a AccountId,
+        amount: &'a U128,
+    },
+    BatchDepositIntoPool {
+        pool_id: &'a u32,
+        sender_id: &'a AccountId,
+        token_ids: &'a Vec<AccountId>,
+        amounts: &'a Vec<U128>,
+        refunds: &'a Vec<U128>,
+        shares: &'a U128,
+    },
+    /// NEAR (`token_id` is null) or tokens sent back to an account
+    Refunded {
+        receiver_id: &'a AccountId,
+        token_id: &'a Option<AccountId>,
+        amount: &'a U128,
+        reason: &'a str,
+    },
+    PendingDepositReclaimed {
+        pool_id: &'a u32,
+        sender_id: &'a AccountId,
+        amounts: &'a Vec<U128>,
     },
     WorkerPinged {
         pool_id: &'a u32,
         worker_id: &'a AccountId,
         timestamp_ms: &'a TimestampMs,
     },
+    PingEventsUpdated {
+        ping_events: &'a PingEvents,
+    },
     ComposeHashApproved {
         compose_hash: &'a String,
+        label: &'a Option<String>,
+        source_repo: &'a Option<String>,
+        source_commit: &'a Option<String>,
+        approved_by: &'a AccountId,
+        expires_at_ms: &'a Option<TimestampMs>,
     },
     ComposeHashRemoved {
         compose_hash: &'a String,
     },
+    PoolComposeHashesUpdated {
+        pool_id: &'a u32,
+        compose_hashes: &'a Option<Vec<String>>,
+    },
+    CodeStaged {
+        code_hash: &'a String,
+        deployable_at_ms: &'a TimestampMs,
+    },
+    CodeDeployed {
+        code_hash: &'a String,
+    },
+    ContractMigrated {
+        from_state_version: &'a u32,
+        to_state_version: &'a u32,
+    },
+    UpgradeDelayChangeScheduled {
+        upgrade_delay_ms: &'a TimestampMs,
+        effective_at_ms: &'a TimestampMs,
+    },
+    UpgradeDelayUpdated {
+        upgrade_delay_ms: &'a TimestampMs,
+    },
+    VaultCodeStaged {
+        code_hash: &'a String,
+    },
+    VaultCodeUnstaged {},
+    VaultGlobalContractPublished {
+        code_hash: &'a String,
+    },
+    VaultGlobalContractPublishFailed {
+        code_hash: &'a String,
+    },
+    PoolVaultUpgraded {
+        pool_id: &'a u32,
+        code_hash: &'a String,
+    },
+    PoolVaultUpgradeFailed {
+        pool_id: &'a u32,
+        code_hash: &'a String,
+    },
+    PoolStatusChanged {
+        pool_id: &'a u32,
+        old_status: &'a PoolStatus,
+        new_status: &'a PoolStatus,
+    },
+    PoolFeeChangeScheduled {
+        pool_id: &'a u32,
+        fee: &'a u32,
+        effective_at_ms: &'a TimestampMs,
+    },
+    PoolFeeUpdated {
+        pool_id: &'a u32,
+        old_fee: &'a u32,
+        new_fee: &'a u32,
+    },
+    PoolWorkerPingTimeoutUpdated {
+        pool_id: &'a u32,
+        worker_ping_timeout_ms: &'a Option<TimestampMs>,
+    },
+    PoolOperatorTransferred {
+        pool_id: &'a u32,
+        old_operator_id: &'a Option<AccountId>,
+        new_operator_id: &'a AccountId,
+    },
+    PoolTokenLimitsUpdated {
+        pool_id: &'a u32,
+        token_id: &'a AccountId,
+        limits: &'a TokenLimits,
+    },
+    PoolDepositModeUpdated {
+        pool_id: &'a u32,
+        deposit_mode: &'a DepositMode,
+    },
+    PoolVaultSingleKeyPolicyUpdated {
+        pool_id: &'a u32,
+        enabled: &'a bool,
+    },
+    PoolCreationPolicyChanged {
+        policy: &'a PoolCreationPolicy,
+    },
+    PoolCreatorAdded {
+        account_id: &'a AccountId,
+    },
+    PoolCreatorRemoved {
+        account_id: &'a AccountId,
+    },
+    TokenAllowed {
+        token_id: &'a AccountId,
+        decimals: &'a u8,
+        min_deposit: &'a U128,
+    },
+    TokenRemoved {
+        token_id: &'a AccountId,
+    },
+    PoolFeeDelayUpdated {
+        pool_fee_delay_ms: &'a TimestampMs,
+    },
+    PendingDepositTimeoutUpdated {
+        pending_deposit_timeout_ms: &'a TimestampMs,
+    },
+    WnearContractUpdated {
+        wnear_contract_id: &'a Option<AccountId>,
+    },
     OwnerChanged {
         old_owner_id: &'a AccountId,
         new_owner_id: &'a AccountId,
@@ -54,14 +219,666 @@ pub enum Event<'a> {
 
 impl Event<'_> {
     pub fn emit(&self) {
-        let json = json!(self);
-        let event_json = json!({
-            "standard": EVENT_STANDARD,
-            "version": EVENT_STANDARD_VERSION,
-            "event": json["event"],
-            "data": [json["data"]]
-        })
-        .to_string();
-        log!("EVENT_JSON:{}", event_json);
+        Self::emit_all(std::slice::from_ref(self));
+    }
+
+    /// Emit the events, logging consecutive events of the same kind as records of one event
+    pub fn emit_all(events: &[Event]) {
+        for log in Self::to_logs(events) {
+            log!("{}", log);
+        }
+    }
+
+    /// NEP-297 log of the event
+    #[cfg(test)]
+    fn to_log(&self) -> String {
+        Self::to_logs(std::slice::from_ref(self)).remove(0)
+    }
+
+    /// NEP-297 logs of the events, one per run of events of the same kind
+    fn to_logs(events: &[Event]) -> Vec<String> {
+        let events: Vec<Value> = events.iter().map(|event| json!(event)).collect();
+        events
+            .chunk_by(|a, b| a["event"] == b["event"])
+            .map(|events| {
+                let event_json = json!({
+                    "standard": EVENT_STANDARD,
+                    "version": EVENT_STANDARD_VERSION,
+                    "event": events[0]["event"],
+                    "data": events.iter().map(|event| &event["data"]).collect::<Vec<_>>(),
+                })
+                .to_string();
+                format!("EVENT_JSON:{}", event_json)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(account_id: &str) -> AccountId {
+        account_id.parse().unwrap()
+    }
+
+    fn public_key() -> PublicKey {
+        "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+            .parse()
+            .unwrap()
+    }
+
+    fn assert_event(event: Event, name: &str, data: Value) {
+        let log = event.to_log();
+        let event_json = log
+            .strip_prefix("EVENT_JSON:")
+            .expect("Event should be prefixed with EVENT_JSON:");
+        let event_json: Value = near_sdk::serde_json::from_str(event_json).unwrap();
+        assert_eq!(
+            event_json,
+            json!({
+                "standard": "solver-registry",
+                "version": "1.2.0",
+                "event": name,
+                "data": [data],
+            })
+        );
+    }
+
+    #[test]
+    fn test_worker_events() {
+        let worker_id = account("worker.near");
+        let public_key = public_key();
+        let compose_hash = "ab".repeat(32);
+        let checksum = "cd".repeat(32);
+
+        assert_event(
+            Event::WorkerRegistered {
+                worker_id: &worker_id,
+                pool_id: &0,
+                public_key: &public_key,
+                compose_hash: &compose_hash,
+                checksum: &checksum,
+            },
+            "worker_registered",
+            json!({
+                "worker_id": "worker.near",
+                "pool_id": 0,
+                "public_key": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+                "compose_hash": compose_hash,
+                "checksum": checksum,
+            }),
+        );
+        assert_event(
+            Event::WorkerRegistrationFailed {
+                worker_id: &worker_id,
+                pool_id: &0,
+                public_key: &public_key,
+                reason: "Failed to add the worker key to the pool vault",
+            },
+            "worker_registration_failed",
+            json!({
+                "worker_id": "worker.near",
+                "pool_id": 0,
+                "public_key": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+                "reason": "Failed to add the worker key to the pool vault",
+            }),
+        );
+        assert_event(
+            Event::WorkerRemoved {
+                worker_id: &worker_id,
+                pool_id: &0,
+                public_key: &public_key,
+                compose_hash: &compose_hash,
+                checksum: &checksum,
+            },
+            "worker_removed",
+            json!({
+                "worker_id": "worker.near",
+                "pool_id": 0,
+                "public_key": "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+                "compose_hash": compose_hash,
+                "checksum": checksum,
+            }),
+        );
+        assert_event(
+            Event::WorkerPinged {
+                pool_id: &0,
+                worker_id: &worker_id,
+                timestamp_ms: &1_700_000_000_000,
+            },
+            "worker_pinged",
+            json!({
+                "pool_id": 0,
+                "worker_id": "worker.near",
+                "timestamp_ms": 1_700_000_000_000u64,
+            }),
+        );
+        assert_event(
+            Event::PoolKeysReconciled {
+                pool_id: &0,
+                removed_keys: &vec![public_key.clone()],
+                failed_keys: &vec![],
+                removed_worker_id: &Some(worker_id.clone()),
+            },
+            "pool_keys_reconciled",
+            json!({
+                "pool_id": 0,
+                "removed_keys": ["ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"],
+                "failed_keys": [],
+                "removed_worker_id": "worker.near",
+            }),
+        );
+    }
+
+    #[test]
+    fn test_ping_events_updated() {
+        assert_event(
+            Event::PingEventsUpdated {
+                ping_events: &PingEvents::LivenessChanges,
+            },
+            "ping_events_updated",
+            json!({"ping_events": "LivenessChanges"}),
+        );
+    }
+
+    #[test]
+    fn test_batch_events() {
+        let alice = account("alice.near");
+        let bob = account("bob.near");
+        let logs = Event::to_logs(&[
+            Event::WorkerPinged {
+                pool_id: &0,
+                worker_id: &alice,
+                timestamp_ms: &1_700_000_000_000,
+            },
+            Event::WorkerPinged {
+                pool_id: &1,
+                worker_id: &bob,
+                timestamp_ms: &1_700_000_000_000,
+            },
+            Event::PoolFeeUpdated {
+                pool_id: &0,
+                old_fee: &300,
+                new_fee: &400,
+            },
+            Event::WorkerPinged {
+                pool_id: &0,
+                worker_id: &alice,
+                timestamp_ms: &1_700_000_001_000,
+            },
+        ]);
+
+        // Consecutive events of the same kind share a log
+        let logs: Vec<Value> = logs
+            .iter()
+            .map(|log| {
+                near_sdk::serde_json::from_str(log.strip_prefix("EVENT_JSON:").unwrap()).unwrap()
+            })
+            .collect();
+        assert_eq!(
+            logs,
+            vec![
+                json!({
+                    "standard": "solver-registry",
+                    "version": "1.2.0",
+                    "event": "worker_pinged",
+                    "data": [
+                        {"pool_id": 0, "worker_id": "alice.near", "timestamp_ms": 1_700_000_000_000u64},
+                        {"pool_id": 1, "worker_id": "bob.near", "timestamp_ms": 1_700_000_000_000u64},
+                    ],
+                }),
+                json!({
+                    "standard": "solver-registry",
+                    "version": "1.2.0",
+                    "event": "pool_fee_updated",
+                    "data": [{"pool_id": 0, "old_fee": 300, "new_fee": 400}],
+                }),
+                json!({
+                    "standard": "solver-registry",
+                    "version": "1.2.0",
+                    "event": "worker_pinged",
+                    "data": [
+                        {"pool_id": 0, "worker_id": "alice.near", "timestamp_ms": 1_700_000_001_000u64},
+                    ],
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pool_events() {
+        let creator_id = account("creator.near");
+        let token_ids = vec![account("wrap.near"), account("usdc.near")];
+
+        assert_event(
+            Event::CreateLiquidityPool {
+                pool_id: &0,
+                token_ids: &token_ids,
+                fee: &300,
+                creator_id: &creator_id,
+            },
+            "create_liquidity_pool",
+            json!({
+                "pool_id": 0,
+                "token_ids": ["wrap.near", "usdc.near"],
+                "fee": 300,
+                "creator_id": "creator.near",
+            }),
+        );
+        assert_event(
+            Event::CreateLiquidityPoolFailed {
+                pool_id: &0,
+                creator_id: &creator_id,
+                refund: &NearToken::from_millinear(1500),
+            },
+            "create_liquidity_pool_failed",
+            json!({
+                "pool_id": 0,
+                "creator_id": "creator.near",
+                "refund": "1500000000000000000000000",
+            }),
+        );
+        assert_event(
+            Event::PoolStatusChanged {
+                pool_id: &0,
+                old_status: &PoolStatus::Active,
+                new_status: &PoolStatus::DepositsClosed,
+            },
+            "pool_status_changed",
+            json!({
+                "pool_id": 0,
+                "old_status": "Active",
+                "new_status": "DepositsClosed",
+            }),
+        );
+        assert_event(
+            Event::PoolComposeHashesUpdated {
+                pool_id: &0,
+                compose_hashes: &Some(vec!["ab".repeat(32)]),
+            },
+            "pool_compose_hashes_updated",
+            json!({
+                "pool_id": 0,
+                "compose_hashes": ["ab".repeat(32)],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_pool_parameter_events() {
+        assert_event(
+            Event::PoolFeeChangeScheduled {
+                pool_id: &0,
+                fee: &400,
+                effective_at_ms: &1_700_000_000_000,
+            },
+            "pool_fee_change_scheduled",
+            json!({"pool_id": 0, "fee": 400, "effective_at_ms": 1_700_000_000_000u64}),
+        );
+        assert_event(
+            Event::PoolFeeUpdated {
+                pool_id: &0,
+                old_fee: &300,
+                new_fee: &400,
+            },
+            "pool_fee_updated",
+            json!({"pool_id": 0, "old_fee": 300, "new_fee": 400}),
+        );
+        assert_event(
+            Event::PoolWorkerPingTimeoutUpdated {
+                pool_id: &0,
+                worker_ping_timeout_ms: &None,
+            },
+            "pool_worker_ping_timeout_updated",
+            json!({"pool_id": 0, "worker_ping_timeout_ms": null}),
+        );
+        assert_event(
+            Event::PoolOperatorTransferred {
+                pool_id: &0,
+                old_operator_id: &Some(account("creator.near")),
+                new_operator_id: &account("operator.near"),
+            },
+            "pool_operator_transferred",
+            json!({
+                "pool_id": 0,
+                "old_operator_id": "creator.near",
+                "new_operator_id": "operator.near",
+            }),
+        );
+        assert_event(
+            Event::PoolTokenLimitsUpdated {
+                pool_id: &0,
+                token_id: &account("usdc.near"),
+                limits: &TokenLimits {
+                    max_pool_amount: Some(U128(1_000_000)),
+                    max_account_amount: None,
+                    min_deposit: Some(U128(10)),
+                },
+            },
+            "pool_token_limits_updated",
+            json!({
+                "pool_id": 0,
+                "token_id": "usdc.near",
+                "limits": {
+                    "max_pool_amount": "1000000",
+                    "max_account_amount": null,
+                    "min_deposit": "10",
+                },
+            }),
+        );
+        assert_event(
+            Event::PoolDepositModeUpdated {
+                pool_id: &0,
+                deposit_mode: &DepositMode::Balanced { tolerance_bps: 50 },
+            },
+            "pool_deposit_mode_updated",
+            json!({"pool_id": 0, "deposit_mode": {"Balanced": {"tolerance_bps": 50}}}),
+        );
+        assert_event(
+            Event::PoolVaultSingleKeyPolicyUpdated {
+                pool_id: &0,
+                enabled: &true,
+            },
+            "pool_vault_single_key_policy_updated",
+            json!({"pool_id": 0, "enabled": true}),
+        );
+    }
+
+    #[test]
+    fn test_deposit_events() {
+        let sender_id = account("alice.near");
+        let token_id = account("usdc.near");
+
+        assert_event(
+            Event::DepositIntoPool {
+                pool_id: &0,
+                sender_id: &sender_id,
+                token_id: &token_id,
+                amount: &U128(1_000_000),
+                refund: &U128(100),
+                shares: &U128(5),
+            },
+            "deposit_into_pool",
+            json!({
+                "pool_id": 0,
+                "sender_id": "alice.near",
+                "token_id": "usdc.near",
+                "amount": "1000000",
+                "refund": "100",
+                "shares": "5",
+            }),
+        );
+        assert_event(
+            Event::DepositHeld {
+                pool_id: &0,
+                sender_id: &sender_id,
+                token_id: &token_id,
+                amount: &U128(1_000_000),
+            },
+            "deposit_held",
+            json!({
+                "pool_id": 0,
+                "sender_id": "alice.near",
+                "token_id": "usdc.near",
+                "amount": "1000000",
+            }),
+        );
+        assert_event(
+            Event::BatchDepositIntoPool {
+                pool_id: &0,
+                sender_id: &sender_id,
+                token_ids: &vec![account("wrap.near"), token_id.clone()],
+                amounts: &vec![U128(2), U128(1_000_000)],
+                refunds: &vec![U128(0), U128(100)],
+                shares: &U128(5),
+            },
+            "batch_deposit_into_pool",
+            json!({
+                "pool_id": 0,
+                "sender_id": "alice.near",
+                "token_ids": ["wrap.near", "usdc.near"],
+                "amounts": ["2", "1000000"],
+                "refunds": ["0", "100"],
+                "shares": "5",
+            }),
+        );
+        assert_event(
+            Event::PendingDepositReclaimed {
+                pool_id: &0,
+                sender_id: &sender_id,
+                amounts: &vec![U128(2), U128(0)],
+            },
+            "pending_deposit_reclaimed",
+            json!({
+                "pool_id": 0,
+                "sender_id": "alice.near",
+                "amounts": ["2", "0"],
+            }),
+        );
+    }
+
+    #[test]
+    fn test_refund_events() {
+        let receiver_id = account("alice.near");
+
+        assert_event(
+            Event::Refunded {
+                receiver_id: &receiver_id,
+                token_id: &None,
+                amount: &U128(1_000),
+                reason: "Excess pool storage deposit",
+            },
+            "refunded",
+            json!({
+                "receiver_id": "alice.near",
+                "token_id": null,
+                "amount": "1000",
+                "reason": "Excess pool storage deposit",
+            }),
+        );
+        assert_event(
+            Event::Refunded {
+                receiver_id: &receiver_id,
+                token_id: &Some(account("wrap.near")),
+                amount: &U128(1_000),
+                reason: "Deposit refund",
+            },
+            "refunded",
+            json!({
+                "receiver_id": "alice.near",
+                "token_id": "wrap.near",
+                "amount": "1000",
+                "reason": "Deposit refund",
+            }),
+        );
+    }
+
+    #[test]
+    fn test_compose_hash_events() {
+        let compose_hash = "ab".repeat(32);
+
+        assert_event(
+            Event::ComposeHashApproved {
+                compose_hash: &compose_hash,
+                label: &Some("v1".to_string()),
+                source_repo: &None,
+                source_commit: &None,
+                approved_by: &account("owner.near"),
+                expires_at_ms: &Some(1_700_000_000_000),
+            },
+            "compose_hash_approved",
+            json!({
+                "compose_hash": compose_hash,
+                "label": "v1",
+                "source_repo": null,
+                "source_commit": null,
+                "approved_by": "owner.near",
+                "expires_at_ms": 1_700_000_000_000u64,
+            }),
+        );
+        assert_event(
+            Event::ComposeHashRemoved {
+                compose_hash: &compose_hash,
+            },
+            "compose_hash_removed",
+            json!({"compose_hash": compose_hash}),
+        );
+    }
+
+    #[test]
+    fn test_upgrade_events() {
+        let code_hash = "ef".repeat(32);
+
+        assert_event(
+            Event::CodeStaged {
+                code_hash: &code_hash,
+                deployable_at_ms: &1_700_000_000_000,
+            },
+            "code_staged",
+            json!({"code_hash": code_hash, "deployable_at_ms": 1_700_000_000_000u64}),
+        );
+        assert_event(
+            Event::CodeDeployed {
+                code_hash: &code_hash,
+            },
+            "code_deployed",
+            json!({"code_hash": code_hash}),
+        );
+        assert_event(
+            Event::ContractMigrated {
+                from_state_version: &1,
+                to_state_version: &2,
+            },
+            "contract_migrated",
+            json!({"from_state_version": 1, "to_state_version": 2}),
+        );
+        assert_event(
+            Event::UpgradeDelayChangeScheduled {
+                upgrade_delay_ms: &0,
+                effective_at_ms: &1_700_000_000_000,
+            },
+            "upgrade_delay_change_scheduled",
+            json!({"upgrade_delay_ms": 0, "effective_at_ms": 1_700_000_000_000u64}),
+        );
+        assert_event(
+            Event::UpgradeDelayUpdated {
+                upgrade_delay_ms: &86_400_000,
+            },
+            "upgrade_delay_updated",
+            json!({"upgrade_delay_ms": 86_400_000}),
+        );
+        assert_event(
+            Event::VaultCodeStaged {
+                code_hash: &code_hash,
+            },
+            "vault_code_staged",
+            json!({"code_hash": code_hash}),
+        );
+        assert_event(
+            Event::VaultCodeUnstaged {},
+            "vault_code_unstaged",
+            json!({}),
+        );
+        assert_event(
+            Event::VaultGlobalContractPublished {
+                code_hash: &code_hash,
+            },
+            "vault_global_contract_published",
+            json!({"code_hash": code_hash}),
+        );
+        assert_event(
+            Event::VaultGlobalContractPublishFailed {
+                code_hash: &code_hash,
+            },
+            "vault_global_contract_publish_failed",
+            json!({"code_hash": code_hash}),
+        );
+        assert_event(
+            Event::PoolVaultUpgraded {
+                pool_id: &0,
+                code_hash: &code_hash,
+            },
+            "pool_vault_upgraded",
+            json!({"pool_id": 0, "code_hash": code_hash}),
+        );
+        assert_event(
+            Event::PoolVaultUpgradeFailed {
+                pool_id: &0,
+                code_hash: &code_hash,
+            },
+            "pool_vault_upgrade_failed",
+            json!({"pool_id": 0, "code_hash": code_hash}),
+        );
+    }
+
+    #[test]
+    fn test_admin_events() {
+        assert_event(
+            Event::PoolCreationPolicyChanged {
+                policy: &PoolCreationPolicy::Whitelisted,
+            },
+            "pool_creation_policy_changed",
+            json!({"policy": "Whitelisted"}),
+        );
+        assert_event(
+            Event::PoolCreatorAdded {
+                account_id: &account("creator.near"),
+            },
+            "pool_creator_added",
+            json!({"account_id": "creator.near"}),
+        );
+        assert_event(
+            Event::PoolCreatorRemoved {
+                account_id: &account("creator.near"),
+            },
+            "pool_creator_removed",
+            json!({"account_id": "creator.near"}),
+        );
+        assert_event(
+            Event::TokenAllowed {
+                token_id: &account("usdc.near"),
+                decimals: &6,
+                min_deposit: &U128(1_000),
+            },
+            "token_allowed",
+            json!({"token_id": "usdc.near", "decimals": 6, "min_deposit": "1000"}),
+        );
+        assert_event(
+            Event::TokenRemoved {
+                token_id: &account("usdc.near"),
+            },
+            "token_removed",
+            json!({"token_id": "usdc.near"}),
+        );
+        assert_event(
+            Event::PoolFeeDelayUpdated {
+                pool_fee_delay_ms: &86_400_000,
+            },
+            "pool_fee_delay_updated",
+            json!({"pool_fee_delay_ms": 86_400_000}),
+        );
+        assert_event(
+            Event::PendingDepositTimeoutUpdated {
+                pending_deposit_timeout_ms: &3_600_000,
+            },
+            "pending_deposit_timeout_updated",
+            json!({"pending_deposit_timeout_ms": 3_600_000}),
+        );
+        assert_event(
+            Event::WnearContractUpdated {
+                wnear_contract_id: &Some(account("wrap.near")),
+            },
+            "wnear_contract_updated",
+            json!({"wnear_contract_id": "wrap.near"}),
+        );
+        assert_event(
+            Event::OwnerChanged {
+                old_owner_id: &account("owner.near"),
+                new_owner_id: &account("new-owner.near"),
+            },
+            "owner_changed",
+            json!({"old_owner_id": "owner.near", "new_owner_id": "new-owner.near"}),
+        );
     }
 }
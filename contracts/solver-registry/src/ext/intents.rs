@@ -0,0 +1,17 @@
+use near_sdk::{ext_contract, json_types::U128, AccountId, PublicKey};
+use std::collections::HashSet;
+
+#[allow(dead_code)]
+#[ext_contract(ext_intents)]
+trait IntentsContract {
+    /// NEP-245 transfer of token balances held in NEAR Intents
+    fn mt_batch_transfer(
+        receiver_id: AccountId,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+        approvals: Option<Vec<Option<(AccountId, u64)>>>,
+        memo: Option<String>,
+    );
+
+    fn public_keys_of(account_id: AccountId) -> HashSet<PublicKey>;
+}
@@ -1,8 +1,42 @@
+use near_sdk::json_types::{Base58CryptoHash, U128};
 use near_sdk::{ext_contract, AccountId, PublicKey};
 
 #[allow(dead_code)]
 #[ext_contract(ext_intents_vault)]
 trait IntentsVaultContract {
+    fn migrate(intents_contract_id: AccountId, public_keys: Vec<PublicKey>);
+    fn use_global_contract(code_hash: Base58CryptoHash);
+    fn add_public_key(public_key: PublicKey);
+    fn remove_public_key(public_key: PublicKey);
+    fn set_single_key_policy(enabled: bool);
+    fn ft_withdraw(
+        token: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: Option<String>,
+    ) -> U128;
+    fn mt_withdraw(
+        token: AccountId,
+        receiver_id: AccountId,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+        memo: Option<String>,
+        msg: Option<String>,
+    ) -> Vec<U128>;
+    fn transfer(
+        receiver_id: AccountId,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+        memo: Option<String>,
+    );
+}
+
+/// Interface of the vaults deployed with v0.1.0. They can't be upgraded, so the registry
+/// keeps calling them with the NEAR Intents account ID.
+#[allow(dead_code)]
+#[ext_contract(ext_legacy_intents_vault)]
+trait LegacyIntentsVaultContract {
     fn add_public_key(intents_contract_id: AccountId, public_key: PublicKey);
     fn remove_public_key(intents_contract_id: AccountId, public_key: PublicKey);
 }
@@ -1,5 +1,9 @@
 mod ft;
+mod intents;
 mod intents_vault;
+mod wnear;
 
 pub use self::ft::*;
+pub use self::intents::*;
 pub use self::intents_vault::*;
+pub use self::wnear::*;
@@ -0,0 +1,8 @@
+use near_sdk::{ext_contract, json_types::U128};
+
+#[allow(dead_code)]
+#[ext_contract(ext_wnear)]
+trait WrappedNearContract {
+    fn near_deposit();
+    fn near_withdraw(amount: U128);
+}
@@ -0,0 +1,82 @@
+//! Storage layouts of previously deployed contract versions. They must never change,
+//! otherwise `migrate` can no longer read the state they describe.
+
+use near_sdk::store::{IterableMap, IterableSet, LookupMap};
+use near_sdk::{near, AccountId, PublicKey};
+
+use crate::pool::{DepositMode, PoolStatus, TokenLimits};
+use crate::types::*;
+use crate::versioned::{Pools, VersionedWorker};
+use crate::{Pool, Worker};
+
+/// Contract state of v0.1.0. Its pool and worker entries are untagged, which the versioned
+/// entries read as their v0.1.0 layout.
+#[near(serializers = [borsh])]
+pub struct ContractV1 {
+    pub owner_id: AccountId,
+    pub intents_contract_id: AccountId,
+    pub pools: Pools,
+    pub approved_compose_hashes: IterableSet<String>,
+    pub worker_by_account_id: IterableMap<AccountId, VersionedWorker>,
+    pub worker_ping_timeout_ms: TimestampMs,
+}
+
+/// Pool entry of v0.1.0
+#[near(serializers = [borsh])]
+pub struct PoolV1 {
+    pub token_ids: Vec<AccountId>,
+    pub amounts: Vec<Balance>,
+    pub fee: u32,
+    pub shares: LookupMap<AccountId, Balance>,
+    pub shares_total_supply: Balance,
+    pub worker_id: Option<AccountId>,
+    pub last_ping_timestamp_ms: TimestampMs,
+}
+
+/// Worker entry of v0.1.0
+#[near(serializers = [borsh])]
+pub struct WorkerV1 {
+    pub pool_id: u32,
+    pub checksum: String,
+    pub compose_hash: String,
+    pub public_key: PublicKey,
+}
+
+impl PoolV1 {
+    /// Upgrade to the current layout. Collections added since v0.1.0 are keyed by the pool ID.
+    pub fn to_pool(&self, pool_id: u32) -> Pool {
+        Pool {
+            token_ids: self.token_ids.clone(),
+            amounts: self.amounts.clone(),
+            fee: self.fee,
+            // v0.1.0 never minted shares, and its pools shared the same prefix
+            shares: LookupMap::new(Prefix::PoolLpShares { pool_id }),
+            shares_total_supply: self.shares_total_supply,
+            worker_id: self.worker_id.clone(),
+            last_ping_timestamp_ms: self.last_ping_timestamp_ms,
+            allowed_compose_hashes: None,
+            vault_code_hash: None,
+            status: PoolStatus::Active,
+            creator_id: None,
+            operator_id: None,
+            worker_ping_timeout_ms: None,
+            pending_fee: None,
+            depositors: IterableMap::new(Prefix::PoolDepositors { pool_id }),
+            token_limits: vec![TokenLimits::default(); self.token_ids.len()],
+            deposit_mode: DepositMode::SingleSided,
+            pending_worker_key: None,
+            worker_key_nonce: 0,
+        }
+    }
+}
+
+impl From<WorkerV1> for Worker {
+    fn from(worker: WorkerV1) -> Self {
+        Self {
+            pool_id: worker.pool_id,
+            checksum: worker.checksum,
+            compose_hash: worker.compose_hash,
+            public_key: worker.public_key,
+        }
+    }
+}
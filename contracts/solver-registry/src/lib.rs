@@ -5,9 +5,11 @@ use hex::decode;
 use near_sdk::{
     assert_one_yocto,
     env::{self, block_timestamp, block_timestamp_ms, sha256},
+    json_types::U128,
     near, require,
-    store::{IterableMap, IterableSet, Vector},
-    AccountId, Gas, NearToken, PanicOnDefault, Promise, PromiseError, PublicKey,
+    store::{IterableMap, IterableSet, LazyOption, LookupMap},
+    AccountId, CryptoHash, Gas, NearToken, PanicOnDefault, Promise, PromiseError, PromiseOrValue,
+    PublicKey,
 };
 use std::str::FromStr;
 
@@ -19,19 +21,30 @@ use crate::attestation::{
     quote::QuoteBytes,
     report_data::ReportData,
 };
+use crate::batch_deposit::DEFAULT_PENDING_DEPOSIT_TIMEOUT_MS;
 use crate::events::*;
 use crate::ext::*;
+use crate::operator::DEFAULT_POOL_FEE_DELAY_MS;
 use crate::pool::*;
 use crate::types::*;
+use crate::versioned::*;
 
 mod admin;
 mod attestation;
+mod batch_deposit;
 mod events;
 mod ext;
+mod legacy;
+mod math;
+mod mt_deposit;
+mod near_deposit;
+mod operator;
 pub mod pool;
+mod reconcile;
 mod token_receiver;
 pub mod types;
 mod upgrade;
+mod versioned;
 mod view;
 
 const GAS_ADD_WORKER_KEY: Gas = Gas::from_tgas(20);
@@ -55,10 +68,24 @@ pub struct Worker {
 pub struct Contract {
     owner_id: AccountId,
     intents_contract_id: AccountId,
-    pools: Vector<Pool>,
-    approved_compose_hashes: IterableSet<String>,
-    worker_by_account_id: IterableMap<AccountId, Worker>,
+    pools: Pools,
+    approved_compose_hashes: IterableMap<String, ComposeHashInfo>,
+    worker_by_account_id: IterableMap<AccountId, VersionedWorker>,
     worker_ping_timeout_ms: TimestampMs,
+    staged_code: LazyOption<Vec<u8>>,
+    staged_code_info: Option<StagedCodeInfo>,
+    upgrade_delay_ms: TimestampMs,
+    pending_upgrade_delay: Option<PendingUpgradeDelay>,
+    staged_vault_code: LazyOption<Vec<u8>>,
+    vault_global_code_hash: Option<CryptoHash>,
+    pool_creation_policy: PoolCreationPolicy,
+    pool_creators: IterableSet<AccountId>,
+    allowed_tokens: IterableMap<AccountId, TokenConfig>,
+    pool_fee_delay_ms: TimestampMs,
+    pending_deposits: LookupMap<(u32, AccountId), PendingDeposit>,
+    pending_deposit_timeout_ms: TimestampMs,
+    wnear_contract_id: Option<AccountId>,
+    ping_events: PingEvents,
 }
 
 #[near]
@@ -70,17 +97,33 @@ impl Contract {
         intents_contract_id: AccountId,
         worker_ping_timeout_ms: TimestampMs,
     ) -> Self {
+        write_state_version();
         Self {
             owner_id,
             intents_contract_id,
-            pools: Vector::new(Prefix::Pools),
-            approved_compose_hashes: IterableSet::new(Prefix::ApprovedComposeHashes),
-            worker_by_account_id: IterableMap::new(Prefix::WorkerByAccountId),
+            pools: Pools::new(Prefix::VersionedPools),
+            approved_compose_hashes: IterableMap::new(Prefix::ComposeHashes),
+            worker_by_account_id: IterableMap::new(Prefix::VersionedWorkers),
             worker_ping_timeout_ms,
+            staged_code: LazyOption::new(Prefix::StagedCode, None),
+            staged_code_info: None,
+            upgrade_delay_ms: 0,
+            pending_upgrade_delay: None,
+            staged_vault_code: LazyOption::new(Prefix::StagedVaultCode, None),
+            vault_global_code_hash: None,
+            pool_creation_policy: PoolCreationPolicy::Open,
+            pool_creators: IterableSet::new(Prefix::PoolCreators),
+            allowed_tokens: IterableMap::new(Prefix::AllowedTokens),
+            pool_fee_delay_ms: DEFAULT_POOL_FEE_DELAY_MS,
+            pending_deposits: LookupMap::new(Prefix::PendingDeposits),
+            pending_deposit_timeout_ms: DEFAULT_PENDING_DEPOSIT_TIMEOUT_MS,
+            wnear_contract_id: None,
+            ping_events: PingEvents::All,
         }
     }
 
     /// Register worker with TEE attestation. The worker needs to running inside a CVM with one of the approved docker compose hashes.
+    /// If the pool has a compose hash allowlist, the compose hash must also be in that list.
     ///
     /// The current TEE attestation module reuses the implementation from [NEAR MPC](https://github.com/near/mpc) TEE attestation with slight change.
     /// Find more details about TEE attestation module in `attestation/README.md`.
@@ -95,6 +138,7 @@ impl Contract {
     ) -> Promise {
         assert_one_yocto();
         let pool = self.pools.get(pool_id).expect("Pool not found");
+        pool.assert_accepts_workers();
 
         // Register new worker is allowed only if there's no active worker and the worker is not already registered
         let worker_id = env::predecessor_account_id();
@@ -130,11 +174,7 @@ impl Contract {
 
         // For now, allow all docker image hashes as we only verify the docker compose hash
         let allowed_docker_image_hashes: Vec<DockerImageHash> = vec![];
-        let allowed_docker_compose_hashes: Vec<DockerComposeHash> = self
-            .approved_compose_hashes
-            .iter()
-            .map(|hash| DockerComposeHash::try_from_hex(hash).expect("Invalid compose hash"))
-            .collect();
+        let allowed_docker_compose_hashes = self.allowed_compose_hashes(pool);
 
         // Verify the attestation
         require!(
@@ -153,32 +193,36 @@ impl Contract {
             .expect("Invalid docker compose hash");
         let docker_compose_hash_hex = docker_compose_hash.as_hex();
 
+        // Track the key until it's added, so that reconciliation doesn't remove it
+        let inactive_worker_id = pool.worker_id.clone();
+        let pool = self.pools.get_mut(pool_id).expect("Pool not found");
+        pool.pending_worker_key = Some(public_key.clone());
+        pool.worker_key_nonce += 1;
+        self.pools.flush();
+
         // Remove the public key of the inactive worker if exists
-        if let Some(inactive_worker_id) = pool.worker_id.as_ref() {
+        if let Some(inactive_worker_id) = inactive_worker_id.as_ref() {
             let inactive_worker = self
                 .worker_by_account_id
                 .get(inactive_worker_id)
                 .expect("Worker not registered");
-            ext_intents_vault::ext(self.get_pool_account_id(pool_id))
-                .with_attached_deposit(NearToken::from_yoctonear(1))
-                .with_static_gas(GAS_REMOVE_WORKER_KEY)
-                .with_unused_gas_weight(0)
-                .remove_public_key(
-                    self.intents_contract_id.clone(),
-                    inactive_worker.public_key.clone(),
-                )
-                .then(
-                    Self::ext(env::current_account_id())
-                        .with_static_gas(GAS_REMOVE_WORKER_KEY_CALLBACK)
-                        .with_unused_gas_weight(0)
-                        .on_inactive_worker_key_removed(
-                            worker_id,
-                            pool_id,
-                            public_key,
-                            docker_compose_hash_hex,
-                            checksum,
-                        ),
-                )
+            self.remove_vault_public_key(
+                pool_id,
+                inactive_worker.public_key.clone(),
+                GAS_REMOVE_WORKER_KEY,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_REMOVE_WORKER_KEY_CALLBACK)
+                    .with_unused_gas_weight(0)
+                    .on_inactive_worker_key_removed(
+                        worker_id,
+                        pool_id,
+                        public_key,
+                        docker_compose_hash_hex,
+                        checksum,
+                    ),
+            )
         } else {
             self.register_new_public_key(
                 worker_id,
@@ -190,6 +234,8 @@ impl Contract {
         }
     }
 
+    /// Returns whether the worker was registered. If the key of the inactive worker couldn't
+    /// be removed, the registration fails as in `on_worker_key_added`.
     #[private]
     pub fn on_inactive_worker_key_removed(
         &mut self,
@@ -199,37 +245,63 @@ impl Contract {
         docker_compose_hash_hex: String,
         checksum: String,
         #[callback_result] call_result: Result<(), PromiseError>,
-    ) -> Promise {
-        if call_result.is_ok() {
-            // remove inactive worker
-            let pool = self.pools.get(pool_id).expect("Pool not found");
-            let inactive_worker_id = pool.worker_id.as_ref().expect("Pool has no worker");
+    ) -> PromiseOrValue<bool> {
+        if call_result.is_err() {
+            self.fail_worker_registration(
+                &worker_id,
+                pool_id,
+                &public_key,
+                "Failed to remove the inactive worker key from the pool vault",
+            );
+            return PromiseOrValue::Value(false);
+        }
+
+        // remove inactive worker, unless it was already removed while its key was being removed
+        let pool = self.pools.get_mut(pool_id).expect("Pool not found");
+        let inactive_worker_id = pool.worker_id.take();
+        pool.worker_key_nonce += 1;
+        let accepts_workers = pool.accepts_workers();
+        self.pools.flush();
+        if let Some(inactive_worker_id) = inactive_worker_id {
             let inactive_worker = self
                 .worker_by_account_id
-                .remove(inactive_worker_id)
+                .remove(&inactive_worker_id)
                 .expect("Worker not registered");
             Event::WorkerRemoved {
-                worker_id: inactive_worker_id,
+                worker_id: &inactive_worker_id,
                 pool_id: &pool_id,
                 public_key: &inactive_worker.public_key,
                 compose_hash: &inactive_worker.compose_hash,
                 checksum: &inactive_worker.checksum,
             }
             .emit();
+        }
 
-            // register new worker and its key
-            self.register_new_public_key(
-                worker_id,
+        // The pool may have started winding down since the registration
+        if !accepts_workers {
+            self.reject_worker_registration(
+                &worker_id,
                 pool_id,
-                public_key,
-                docker_compose_hash_hex,
-                checksum,
-            )
-        } else {
-            env::panic_str("Failed to remove inactive worker key");
+                &public_key,
+                "Pool no longer accepts workers",
+            );
+            return PromiseOrValue::Value(false);
         }
+
+        // register new worker and its key
+        self.register_new_public_key(
+            worker_id,
+            pool_id,
+            public_key,
+            docker_compose_hash_hex,
+            checksum,
+        )
+        .into()
     }
 
+    /// Returns whether the worker was registered. If its key couldn't be added to the pool
+    /// vault, `WorkerRegistrationFailed` is emitted and the attached deposit is refunded.
+    /// If the pool no longer accepts workers, the added key is removed again.
     #[private]
     pub fn on_worker_key_added(
         &mut self,
@@ -239,33 +311,57 @@ impl Contract {
         compose_hash: String,
         checksum: String,
         #[callback_result] call_result: Result<(), PromiseError>,
-    ) {
-        if call_result.is_ok() {
-            self.worker_by_account_id.insert(
-                worker_id.clone(),
-                Worker {
-                    pool_id,
-                    checksum: checksum.clone(),
-                    compose_hash: compose_hash.clone(),
-                    public_key: public_key.clone(),
-                },
+    ) -> bool {
+        if call_result.is_err() {
+            self.fail_worker_registration(
+                &worker_id,
+                pool_id,
+                &public_key,
+                "Failed to add the worker key to the pool vault",
             );
+            return false;
+        }
 
-            // Update the pool with the worker ID and last ping timestamp
-            let pool = self.pools.get_mut(pool_id).expect("Pool not found");
-            pool.worker_id = Some(worker_id.clone());
-            pool.last_ping_timestamp_ms = block_timestamp_ms();
-            self.pools.flush();
+        let pool = self.pools.get(pool_id).expect("Pool not found");
+        if !pool.accepts_workers() {
+            self.remove_vault_public_key(pool_id, public_key.clone(), GAS_REMOVE_WORKER_KEY);
+            self.reject_worker_registration(
+                &worker_id,
+                pool_id,
+                &public_key,
+                "Pool no longer accepts workers",
+            );
+            return false;
+        }
 
-            Event::WorkerRegistered {
-                worker_id: &worker_id,
-                pool_id: &pool_id,
-                public_key: &public_key,
-                compose_hash: &compose_hash,
-                checksum: &checksum,
+        self.worker_by_account_id.insert(
+            worker_id.clone(),
+            Worker {
+                pool_id,
+                checksum: checksum.clone(),
+                compose_hash: compose_hash.clone(),
+                public_key: public_key.clone(),
             }
-            .emit();
+            .into(),
+        );
+
+        // Update the pool with the worker ID and last ping timestamp
+        self.clear_pending_worker_key(pool_id, &public_key);
+        let pool = self.pools.get_mut(pool_id).expect("Pool not found");
+        pool.worker_id = Some(worker_id.clone());
+        pool.last_ping_timestamp_ms = block_timestamp_ms();
+        self.pools.flush();
+
+        Event::WorkerRegistered {
+            worker_id: &worker_id,
+            pool_id: &pool_id,
+            public_key: &public_key,
+            compose_hash: &compose_hash,
+            checksum: &checksum,
         }
+        .emit();
+
+        true
     }
 
     /// Heartbeat to notify the pool that the worker is still alive.
@@ -274,7 +370,8 @@ impl Contract {
         let worker = self
             .get_worker(worker_id.clone())
             .expect("Worker not found");
-        self.assert_approved_compose_hash(&worker.compose_hash);
+        let pool = self.pools.get(worker.pool_id).expect("Pool not found");
+        self.assert_compose_hash_allowed(pool, &worker.compose_hash);
 
         let pool = self.pools.get_mut(worker.pool_id).expect("Pool not found");
         let registered_worker_id = pool.worker_id.as_ref().expect("Worker not registered");
@@ -283,26 +380,44 @@ impl Contract {
             "Only the registered worker can ping"
         );
 
+        let was_active = pool.has_active_worker(self.worker_ping_timeout_ms);
         pool.last_ping_timestamp_ms = block_timestamp_ms();
         self.pools.flush();
 
-        Event::WorkerPinged {
-            pool_id: &worker.pool_id,
-            worker_id: &worker_id,
-            timestamp_ms: &block_timestamp_ms(),
+        if self.ping_events == PingEvents::All || !was_active {
+            Event::WorkerPinged {
+                pool_id: &worker.pool_id,
+                worker_id: &worker_id,
+                timestamp_ms: &block_timestamp_ms(),
+            }
+            .emit();
         }
-        .emit();
     }
 }
 
 impl Contract {
-    fn assert_approved_compose_hash(&self, compose_hash: &String) {
+    fn assert_compose_hash_allowed(&self, pool: &Pool, compose_hash: &String) {
+        let approved = self
+            .approved_compose_hashes
+            .get(compose_hash)
+            .is_some_and(|info| !info.is_expired());
         require!(
-            self.approved_compose_hashes.contains(compose_hash),
+            approved && pool.allows_compose_hash(compose_hash),
             "Invalid compose hash"
         );
     }
 
+    /// Approved and unexpired compose hashes that are allowed to register for the pool
+    fn allowed_compose_hashes(&self, pool: &Pool) -> Vec<DockerComposeHash> {
+        self.approved_compose_hashes
+            .values()
+            .filter(|info| !info.is_expired() && pool.allows_compose_hash(&info.compose_hash))
+            .map(|info| {
+                DockerComposeHash::try_from_hex(&info.compose_hash).expect("Invalid compose hash")
+            })
+            .collect()
+    }
+
     fn find_approved_compose_hash(
         &self,
         tcb_info: &TcbInfo,
@@ -322,6 +437,53 @@ impl Contract {
             .cloned()
     }
 
+    /// Stop tracking the key of a registration, unless another registration has replaced it
+    fn clear_pending_worker_key(&mut self, pool_id: u32, public_key: &PublicKey) {
+        let pool = self.pools.get_mut(pool_id).expect("Pool not found");
+        if pool.pending_worker_key.as_ref() == Some(public_key) {
+            pool.pending_worker_key = None;
+        }
+        pool.worker_key_nonce += 1;
+        self.pools.flush();
+    }
+
+    fn reject_worker_registration(
+        &mut self,
+        worker_id: &AccountId,
+        pool_id: u32,
+        public_key: &PublicKey,
+        reason: &str,
+    ) {
+        self.clear_pending_worker_key(pool_id, public_key);
+        Event::WorkerRegistrationFailed {
+            worker_id,
+            pool_id: &pool_id,
+            public_key,
+            reason,
+        }
+        .emit();
+    }
+
+    /// Reject a registration whose vault call failed, and refund the attached deposit, which
+    /// the failed call returned to the registry
+    fn fail_worker_registration(
+        &mut self,
+        worker_id: &AccountId,
+        pool_id: u32,
+        public_key: &PublicKey,
+        reason: &str,
+    ) {
+        self.reject_worker_registration(worker_id, pool_id, public_key, reason);
+        Promise::new(worker_id.clone()).transfer(NearToken::from_yoctonear(1));
+        Event::Refunded {
+            receiver_id: worker_id,
+            token_id: &None,
+            amount: &U128(1),
+            reason: "Worker registration failed",
+        }
+        .emit();
+    }
+
     fn register_new_public_key(
         &mut self,
         worker_id: AccountId,
@@ -331,11 +493,7 @@ impl Contract {
         checksum: String,
     ) -> Promise {
         // Add the public key to the intents vault
-        ext_intents_vault::ext(self.get_pool_account_id(pool_id))
-            .with_attached_deposit(NearToken::from_yoctonear(1))
-            .with_static_gas(GAS_ADD_WORKER_KEY)
-            .with_unused_gas_weight(0)
-            .add_public_key(self.intents_contract_id.clone(), public_key.clone())
+        self.add_vault_public_key(pool_id, public_key.clone(), GAS_ADD_WORKER_KEY)
             .then(
                 Self::ext(env::current_account_id())
                     .with_static_gas(GAS_ADD_WORKER_KEY_CALLBACK)
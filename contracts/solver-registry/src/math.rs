@@ -0,0 +1,80 @@
+use near_sdk::require;
+
+/// `a * b / c` rounded down. The intermediate product doesn't overflow.
+pub fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    require!(c > 0, "Division by zero");
+    let (hi, lo) = mul_wide(a, b);
+    require!(hi < c, "Multiplication overflow");
+
+    // Long division of the 256-bit product, one bit at a time
+    let mut rem = hi;
+    let mut quotient = 0u128;
+    for i in (0..128).rev() {
+        let carry = rem >> 127;
+        rem = (rem << 1) | ((lo >> i) & 1);
+        quotient <<= 1;
+        if carry == 1 || rem >= c {
+            rem = rem.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    quotient
+}
+
+/// Full 256-bit product as (high, low) halves
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+
+    let lo_lo = a_lo * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_lo = a_hi * b_lo;
+    let hi_hi = a_hi * b_hi;
+
+    let mid = (lo_lo >> 64) + (lo_hi & MASK) + (hi_lo & MASK);
+    let lo = (lo_lo & MASK) | (mid << 64);
+    let hi = hi_hi + (lo_hi >> 64) + (hi_lo >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mul_div() {
+        assert_eq!(mul_div(6, 7, 3), 14);
+        assert_eq!(mul_div(7, 3, 2), 10);
+        assert_eq!(mul_div(0, u128::MAX, 1), 0);
+        assert_eq!(mul_div(1, 1, u128::MAX), 0);
+    }
+
+    #[test]
+    fn test_mul_div_max_operands() {
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), u128::MAX);
+        assert_eq!(mul_div(u128::MAX, 1, 1), u128::MAX);
+        assert_eq!(mul_div(u128::MAX, 2, 4), u128::MAX / 2);
+        assert_eq!(mul_div(u128::MAX, u128::MAX - 1, u128::MAX), u128::MAX - 1);
+        // The product doesn't fit in 128 bits, but the result does
+        assert_eq!(mul_div(1 << 127, 1 << 127, 1 << 127), 1 << 127);
+    }
+
+    #[test]
+    #[should_panic(expected = "Division by zero")]
+    fn test_mul_div_zero_divisor() {
+        mul_div(1, 1, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Multiplication overflow")]
+    fn test_mul_div_overflow() {
+        mul_div(u128::MAX, 2, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Multiplication overflow")]
+    fn test_mul_div_max_operands_overflow() {
+        mul_div(u128::MAX, u128::MAX, u128::MAX - 1);
+    }
+}
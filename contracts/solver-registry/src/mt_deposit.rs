@@ -0,0 +1,138 @@
+use near_sdk::json_types::U128;
+use near_sdk::{near, require, AccountId, Gas, NearToken, PromiseError, PromiseOrValue};
+
+use crate::events::Event;
+use crate::ext::ext_intents;
+use crate::pool::{ERR_BAD_TOKEN_ID, ERR_INVALID_AMOUNT, ERR_POOL_NOT_FOUND};
+use crate::*;
+
+const GAS_MT_DEPOSIT_CALLBACK: Gas = Gas::from_tgas(20);
+
+/// Prefix of NEP-141 token IDs in NEAR Intents
+const NEP141_TOKEN_PREFIX: &str = "nep141:";
+
+#[near]
+impl Contract {
+    /// `deposit` of each pool token was transferred by the sender in NEAR Intents, of which
+    /// `accepted` was forwarded to the pool for `shares`. Returns the refund of each of the
+    /// transferred `token_ids` to `mt_on_transfer`.
+    #[private]
+    #[allow(clippy::too_many_arguments)]
+    pub fn on_mt_deposit_into_pool(
+        &mut self,
+        pool_id: u32,
+        sender_id: AccountId,
+        token_ids: Vec<String>,
+        deposit: Vec<U128>,
+        accepted: Vec<U128>,
+        shares: U128,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) -> Vec<U128> {
+        // The batch transfer either moves all accepted amounts or none of them
+        let transferred = call_result.is_ok();
+
+        let pool = self.pools.get_mut(pool_id).expect(ERR_POOL_NOT_FOUND);
+        if !transferred {
+            for (i, accepted) in accepted.iter().enumerate() {
+                pool.release_deposit(&sender_id, i, accepted.0);
+            }
+            pool.release_shares(shares.0);
+        } else if shares.0 > 0 {
+            pool.mint_shares(&sender_id, shares.0);
+        }
+        let shares = U128(if transferred { shares.0 } else { 0 });
+        let pool_token_ids = pool.token_ids.clone();
+        self.pools.flush();
+
+        let refunds: Vec<U128> = deposit
+            .iter()
+            .zip(&accepted)
+            .map(|(amount, accepted)| {
+                U128(if transferred {
+                    amount.0 - accepted.0
+                } else {
+                    amount.0
+                })
+            })
+            .collect();
+
+        Event::BatchDepositIntoPool {
+            pool_id: &pool_id,
+            sender_id: &sender_id,
+            token_ids: &pool_token_ids,
+            amounts: &deposit,
+            refunds: &refunds,
+            shares: &shares,
+        }
+        .emit();
+
+        token_ids
+            .iter()
+            .map(|token_id| refunds[pool_token_index(&pool_token_ids, token_id)])
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Deposit token balances transferred to the registry in NEAR Intents into the pool,
+    /// by forwarding them to the pool account. All transferred tokens are deposited together.
+    pub(crate) fn mt_deposit_into_pool(
+        &mut self,
+        pool_id: u32,
+        sender_id: &AccountId,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+    ) -> PromiseOrValue<Vec<U128>> {
+        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        let mut deposit = vec![0; pool.token_ids.len()];
+        for (token_id, amount) in token_ids.iter().zip(&amounts) {
+            require!(amount.0 > 0, ERR_INVALID_AMOUNT);
+            let token_index = pool_token_index(&pool.token_ids, token_id);
+            require!(deposit[token_index] == 0, "Duplicate token in transfer");
+            deposit[token_index] = amount.0;
+        }
+
+        let (accepted, shares) = self.reserve_deposit(pool_id, sender_id, &deposit);
+
+        // forward the accepted amounts to the pool account within NEAR Intents
+        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        let (transfer_token_ids, transfer_amounts): (Vec<String>, Vec<U128>) = pool
+            .token_ids
+            .iter()
+            .zip(&accepted)
+            .filter(|(_, amount)| **amount > 0)
+            .map(|(token_id, amount)| (format!("{NEP141_TOKEN_PREFIX}{token_id}"), U128(*amount)))
+            .unzip();
+
+        ext_intents::ext(self.intents_contract_id.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .mt_batch_transfer(
+                self.get_pool_account_id(pool_id),
+                transfer_token_ids,
+                transfer_amounts,
+                None,
+                Some("deposit into pool".to_string()),
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_MT_DEPOSIT_CALLBACK)
+                    .on_mt_deposit_into_pool(
+                        pool_id,
+                        sender_id.clone(),
+                        token_ids,
+                        deposit.into_iter().map(U128).collect(),
+                        accepted.into_iter().map(U128).collect(),
+                        U128(shares.iter().sum()),
+                    ),
+            )
+            .into()
+    }
+}
+
+/// Index of a NEAR Intents token ID in the pool tokens. Only NEP-141 tokens are supported.
+fn pool_token_index(pool_token_ids: &[AccountId], token_id: &str) -> usize {
+    token_id
+        .strip_prefix(NEP141_TOKEN_PREFIX)
+        .and_then(|token_id| pool_token_ids.iter().position(|id| id == token_id))
+        .expect(ERR_BAD_TOKEN_ID)
+}
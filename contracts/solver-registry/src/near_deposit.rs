@@ -0,0 +1,185 @@
+use near_sdk::json_types::U128;
+use near_sdk::{near, require, AccountId, Gas, NearToken, Promise, PromiseError, PromiseOrValue};
+
+use crate::events::Event;
+use crate::ext::{ext_ft, ext_wnear};
+use crate::pool::{ERR_BAD_TOKEN_ID, ERR_INVALID_AMOUNT, ERR_POOL_NOT_FOUND};
+use crate::*;
+
+const GAS_NEAR_DEPOSIT: Gas = Gas::from_tgas(10);
+const GAS_NEAR_WITHDRAW: Gas = Gas::from_tgas(10);
+const GAS_NEAR_WRAPPED_CALLBACK: Gas = Gas::from_tgas(20);
+const GAS_NEAR_DEPOSIT_RESOLVE_CALLBACK: Gas = Gas::from_tgas(30);
+const GAS_NEAR_REFUND_CALLBACK: Gas = Gas::from_tgas(10);
+
+const ERR_WNEAR_NOT_CONFIGURED: &str = "wNEAR contract is not configured";
+
+#[near]
+impl Contract {
+    /// Deposit the attached NEAR into a pool with wNEAR. The NEAR is wrapped through the
+    /// configured wNEAR contract and forwarded to NEAR Intents for the pool account.
+    /// The part of the deposit that isn't accepted by the pool is refunded in NEAR.
+    #[payable]
+    pub fn deposit_near_into_pool(&mut self, pool_id: u32) -> Promise {
+        let wnear_id = self
+            .wnear_contract_id
+            .clone()
+            .expect(ERR_WNEAR_NOT_CONFIGURED);
+        let sender_id = env::predecessor_account_id();
+        let amount = env::attached_deposit().as_yoctonear();
+        require!(amount > 0, ERR_INVALID_AMOUNT);
+
+        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        require!(pool.token_ids.contains(&wnear_id), ERR_BAD_TOKEN_ID);
+        let token_index = pool.token_index(&wnear_id);
+
+        let mut deposit = vec![0; pool.token_ids.len()];
+        deposit[token_index] = amount;
+        let (accepted, shares) = self.reserve_deposit(pool_id, &sender_id, &deposit);
+        let deposit_amount = accepted[token_index];
+
+        // Refund the excess right away, it's never wrapped
+        if amount > deposit_amount {
+            Promise::new(sender_id.clone())
+                .transfer(NearToken::from_yoctonear(amount - deposit_amount));
+
+            Event::Refunded {
+                receiver_id: &sender_id,
+                token_id: &None,
+                amount: &U128(amount - deposit_amount),
+                reason: "Deposit not accepted by the pool",
+            }
+            .emit();
+        }
+
+        ext_wnear::ext(wnear_id.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(deposit_amount))
+            .with_static_gas(GAS_NEAR_DEPOSIT)
+            .with_unused_gas_weight(0)
+            .near_deposit()
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_NEAR_WRAPPED_CALLBACK)
+                    .on_near_wrapped(
+                        pool_id,
+                        wnear_id,
+                        sender_id,
+                        U128(deposit_amount),
+                        U128(shares[token_index]),
+                    ),
+            )
+    }
+
+    /// Forward the wrapped NEAR to NEAR Intents, or release the reserved capacity and
+    /// refund the NEAR if it couldn't be wrapped.
+    #[private]
+    pub fn on_near_wrapped(
+        &mut self,
+        pool_id: u32,
+        wnear_id: AccountId,
+        sender_id: AccountId,
+        amount: U128,
+        shares: U128,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) -> PromiseOrValue<U128> {
+        if call_result.is_err() {
+            let pool = self.pools.get_mut(pool_id).expect(ERR_POOL_NOT_FOUND);
+            let token_index = pool.token_index(&wnear_id);
+            pool.release_deposit(&sender_id, token_index, amount.0);
+            pool.release_shares(shares.0);
+            self.pools.flush();
+
+            Promise::new(sender_id.clone()).transfer(NearToken::from_yoctonear(amount.0));
+
+            Event::DepositIntoPool {
+                pool_id: &pool_id,
+                sender_id: &sender_id,
+                token_id: &wnear_id,
+                amount: &amount,
+                refund: &amount,
+                shares: &U128(0),
+            }
+            .emit();
+            return PromiseOrValue::Value(amount);
+        }
+
+        // deposit the wrapped NEAR into NEAR Intents, shares are minted as for a wNEAR deposit
+        ext_ft::ext(wnear_id.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .ft_transfer_call(
+                self.intents_contract_id.clone(),
+                amount,
+                Some("deposit into pool".to_string()),
+                self.get_pool_account_id(pool_id).to_string(),
+            )
+            .then(Self::ext(env::current_account_id()).on_deposit_into_pool(
+                pool_id,
+                wnear_id.clone(),
+                sender_id.clone(),
+                amount,
+                amount,
+                shares,
+            ))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_NEAR_DEPOSIT_RESOLVE_CALLBACK)
+                    .with_unused_gas_weight(0)
+                    .on_near_deposit_resolved(wnear_id, sender_id),
+            )
+            .into()
+    }
+
+    /// Unwrap the wNEAR that wasn't used by the deposit, so that it's refunded in NEAR
+    #[private]
+    pub fn on_near_deposit_resolved(
+        &self,
+        wnear_id: AccountId,
+        sender_id: AccountId,
+        #[callback_result] refund: Result<U128, PromiseError>,
+    ) -> U128 {
+        let refund = refund.unwrap_or(U128(0));
+        if refund.0 > 0 {
+            ext_wnear::ext(wnear_id.clone())
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(GAS_NEAR_WITHDRAW)
+                .with_unused_gas_weight(0)
+                .near_withdraw(refund)
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(GAS_NEAR_REFUND_CALLBACK)
+                        .with_unused_gas_weight(0)
+                        .on_near_refund_unwrapped(wnear_id, sender_id, refund),
+                );
+        }
+        refund
+    }
+
+    /// Send the unwrapped refund, or the wNEAR itself if it couldn't be unwrapped
+    #[private]
+    pub fn on_near_refund_unwrapped(
+        &self,
+        wnear_id: AccountId,
+        sender_id: AccountId,
+        amount: U128,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) {
+        // The refund is sent in wNEAR if it couldn't be unwrapped
+        let token_id = if call_result.is_ok() {
+            Promise::new(sender_id.clone()).transfer(NearToken::from_yoctonear(amount.0));
+            None
+        } else {
+            ext_ft::ext(wnear_id.clone())
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .ft_transfer(sender_id.clone(), amount, None);
+            Some(wnear_id)
+        };
+
+        Event::Refunded {
+            receiver_id: &sender_id,
+            token_id: &token_id,
+            amount: &amount,
+            reason: "Deposit refund",
+        }
+        .emit();
+    }
+}
@@ -0,0 +1,200 @@
+use crate::*;
+use near_sdk::near;
+
+/// Default time between scheduling and applying a pool fee change
+pub(crate) const DEFAULT_POOL_FEE_DELAY_MS: TimestampMs = 24 * 60 * 60 * 1000; // 1 day
+/// Maximum change of a pool fee in a single update, in basis points
+const MAX_POOL_FEE_CHANGE: u32 = 100;
+const GAS_SET_SINGLE_KEY_POLICY: Gas = Gas::from_tgas(10);
+const GAS_SET_SINGLE_KEY_POLICY_CALLBACK: Gas = Gas::from_tgas(5);
+
+#[near]
+impl Contract {
+    /// Schedule a pool fee change in basis points. The fee can move by at most 1% at a time,
+    /// and the change can be applied with `apply_pool_fee` after the pool fee delay.
+    pub fn set_pool_fee(&mut self, pool_id: u32, fee: u32) {
+        let pool = self.pools.get(pool_id).expect("Pool not found");
+        self.assert_pool_operator(pool);
+        require!(fee < 10_000, "Fee must be less than 100%");
+        require!(
+            fee.abs_diff(pool.fee) <= MAX_POOL_FEE_CHANGE,
+            "Fee change is too large"
+        );
+
+        let effective_at_ms = block_timestamp_ms() + self.pool_fee_delay_ms;
+        let pool = self.pools.get_mut(pool_id).expect("Pool not found");
+        pool.pending_fee = Some(PendingPoolFee {
+            fee,
+            effective_at_ms,
+        });
+        self.pools.flush();
+
+        Event::PoolFeeChangeScheduled {
+            pool_id: &pool_id,
+            fee: &fee,
+            effective_at_ms: &effective_at_ms,
+        }
+        .emit();
+    }
+
+    /// Apply the scheduled pool fee change once the delay has passed
+    pub fn apply_pool_fee(&mut self, pool_id: u32) {
+        let pool = self.pools.get_mut(pool_id).expect("Pool not found");
+        let pending_fee = pool.pending_fee.take().expect("No pending fee change");
+        require!(
+            block_timestamp_ms() >= pending_fee.effective_at_ms,
+            "Fee change is not effective yet"
+        );
+
+        let old_fee = pool.fee;
+        pool.fee = pending_fee.fee;
+        self.pools.flush();
+
+        Event::PoolFeeUpdated {
+            pool_id: &pool_id,
+            old_fee: &old_fee,
+            new_fee: &pending_fee.fee,
+        }
+        .emit();
+    }
+
+    /// Set the worker ping timeout of a pool. Pass `None` to use the registry default.
+    pub fn set_pool_worker_ping_timeout_ms(
+        &mut self,
+        pool_id: u32,
+        worker_ping_timeout_ms: Option<TimestampMs>,
+    ) {
+        let pool = self.pools.get(pool_id).expect("Pool not found");
+        self.assert_pool_operator(pool);
+        require!(
+            worker_ping_timeout_ms != Some(0),
+            "Ping timeout must be > 0"
+        );
+
+        let pool = self.pools.get_mut(pool_id).expect("Pool not found");
+        pool.worker_ping_timeout_ms = worker_ping_timeout_ms;
+        self.pools.flush();
+
+        Event::PoolWorkerPingTimeoutUpdated {
+            pool_id: &pool_id,
+            worker_ping_timeout_ms: &worker_ping_timeout_ms,
+        }
+        .emit();
+    }
+
+    /// Restrict the approved docker compose hashes that can register for a pool.
+    /// Pass `None` to allow all approved compose hashes.
+    pub fn set_pool_compose_hashes(&mut self, pool_id: u32, compose_hashes: Option<Vec<String>>) {
+        let pool = self.pools.get(pool_id).expect("Pool not found");
+        self.assert_pool_operator(pool);
+        if let Some(compose_hashes) = compose_hashes.as_ref() {
+            for compose_hash in compose_hashes {
+                require!(
+                    self.approved_compose_hashes.contains_key(compose_hash),
+                    "Compose hash not approved"
+                );
+            }
+        }
+
+        let pool = self.pools.get_mut(pool_id).expect("Pool not found");
+        pool.allowed_compose_hashes = compose_hashes.clone();
+        self.pools.flush();
+
+        Event::PoolComposeHashesUpdated {
+            pool_id: &pool_id,
+            compose_hashes: &compose_hashes,
+        }
+        .emit();
+    }
+
+    /// Set how deposits are accepted and valued
+    pub fn set_pool_deposit_mode(&mut self, pool_id: u32, deposit_mode: DepositMode) {
+        let pool = self.pools.get(pool_id).expect("Pool not found");
+        self.assert_pool_operator(pool);
+        if let DepositMode::Balanced { tolerance_bps } = deposit_mode {
+            require!(tolerance_bps < 10_000, "Tolerance must be less than 100%");
+        }
+
+        let pool = self.pools.get_mut(pool_id).expect("Pool not found");
+        pool.deposit_mode = deposit_mode;
+        self.pools.flush();
+
+        Event::PoolDepositModeUpdated {
+            pool_id: &pool_id,
+            deposit_mode: &deposit_mode,
+        }
+        .emit();
+    }
+
+    /// Allow at most one authorized key in the pool vault. Worker keys are replaced by removing
+    /// the inactive worker key first, so this doesn't block worker replacement.
+    #[payable]
+    pub fn set_pool_vault_single_key_policy(&mut self, pool_id: u32, enabled: bool) -> Promise {
+        assert_one_yocto();
+        let pool = self.pools.get(pool_id).expect("Pool not found");
+        self.assert_pool_operator(pool);
+        require!(
+            !pool.has_legacy_vault(),
+            "The pool vault doesn't support a key policy"
+        );
+
+        ext_intents_vault::ext(self.get_pool_account_id(pool_id))
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_SET_SINGLE_KEY_POLICY)
+            .set_single_key_policy(enabled)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_SET_SINGLE_KEY_POLICY_CALLBACK)
+                    .with_unused_gas_weight(0)
+                    .on_pool_vault_single_key_policy_set(pool_id, enabled),
+            )
+    }
+
+    /// Fails if the vault rejected the policy, so that the operator sees the failure
+    #[private]
+    pub fn on_pool_vault_single_key_policy_set(
+        &mut self,
+        pool_id: u32,
+        enabled: bool,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) {
+        require!(call_result.is_ok(), "Failed to set the vault key policy");
+
+        Event::PoolVaultSingleKeyPolicyUpdated {
+            pool_id: &pool_id,
+            enabled: &enabled,
+        }
+        .emit();
+    }
+
+    /// Hand the pool over to a new operator
+    pub fn transfer_pool_operator(&mut self, pool_id: u32, new_operator_id: AccountId) {
+        let pool = self.pools.get(pool_id).expect("Pool not found");
+        self.assert_pool_operator(pool);
+
+        let pool = self.pools.get_mut(pool_id).expect("Pool not found");
+        let old_operator_id = pool.operator_id.replace(new_operator_id.clone());
+        self.pools.flush();
+
+        Event::PoolOperatorTransferred {
+            pool_id: &pool_id,
+            old_operator_id: &old_operator_id,
+            new_operator_id: &new_operator_id,
+        }
+        .emit();
+    }
+}
+
+impl Contract {
+    /// The owner can manage every pool, the operator only its own.
+    pub(crate) fn is_pool_operator(&self, pool: &Pool, account_id: &AccountId) -> bool {
+        account_id == &self.owner_id || pool.is_operator(account_id)
+    }
+
+    pub(crate) fn assert_pool_operator(&self, pool: &Pool) {
+        require!(
+            self.is_pool_operator(pool, &env::predecessor_account_id()),
+            "Only the owner or the pool operator can manage the pool"
+        );
+    }
+}
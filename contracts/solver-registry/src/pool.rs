@@ -1,25 +1,154 @@
 use near_sdk::json_types::U128;
 // use near_sdk::json_types::U128;
-use near_sdk::store::LookupMap;
-use near_sdk::{near, require, AccountId, Gas, NearToken, PromiseError, PromiseOrValue};
+use near_sdk::store::{IterableMap, LookupMap};
+use near_sdk::{near, require, AccountId, Gas, NearToken, PromiseError, PromiseOrValue, PublicKey};
 
 use crate::events::Event;
-use crate::ext::ext_ft;
+use crate::ext::{ext_ft, ext_intents_vault, ext_legacy_intents_vault};
+use crate::math::mul_div;
 use crate::*;
 
+/// Vault code embedded at build time, deployed to new pools unless a newer vault code is staged.
+pub(crate) const INTENTS_VAULT_WASM: &[u8] =
+    include_bytes!("../../intents-vault/res/intents_vault.wasm");
+
+/// Storage for a pool account holding a full copy of the vault code
 const CREATE_POOL_STORAGE_DEPOSIT: NearToken =
     NearToken::from_yoctonear(1_500_000_000_000_000_000_000_000); // 1.5 NEAR
+/// Storage for a pool account referencing the vault global contract by its code hash
+const CREATE_POOL_GLOBAL_STORAGE_DEPOSIT: NearToken =
+    NearToken::from_yoctonear(100_000_000_000_000_000_000_000); // 0.1 NEAR
+const GAS_INIT_POOL_VAULT: Gas = Gas::from_tgas(5);
 const GAS_CREATE_POOL_CALLBACK: Gas = Gas::from_tgas(10);
+const GAS_REVOKE_WORKER_KEY: Gas = Gas::from_tgas(20);
+const GAS_REVOKE_WORKER_KEY_CALLBACK: Gas = Gas::from_tgas(10);
+
+pub(crate) const ERR_POOL_NOT_FOUND: &str = "Pool not found";
+pub(crate) const ERR_BAD_TOKEN_ID: &str = "Token doesn't exist in pool";
+pub(crate) const ERR_INVALID_AMOUNT: &str = "Amount must be > 0";
+const ERR_POOL_NOT_ACTIVE: &str = "Pool is not active";
+const ERR_POOL_CLOSED: &str = "Pool is closed";
+const ERR_INVALID_STATUS_TRANSITION: &str = "Invalid pool status transition";
+const ERR_TOKEN_NOT_ALLOWED: &str = "Token is not allowed";
+const ERR_DEPOSIT_TOO_SMALL: &str = "Deposit is below the minimum";
+const ERR_DEPOSIT_CAP_REACHED: &str = "Deposit cap reached";
+const ERR_DEPOSIT_RATIO: &str = "Deposit doesn't match the pool ratio";
+const ERR_UNSEEDED_TOKEN: &str =
+    "Only the holder of all shares can deposit a token the pool doesn't hold";
+const ERR_POOL_NOT_SEEDED: &str =
+    "Only the pool operator can deposit a single token until the pool holds all tokens";
+
+/// Shares minted by the first deposit into a pool
+const INIT_SHARES_SUPPLY: Balance = 1_000_000_000_000_000_000_000_000; // 1e24
+
+/// Who can call `create_liquidity_pool`
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolCreationPolicy {
+    /// Anyone can create pools.
+    Open,
+    /// Only whitelisted pool creators and the owner can create pools.
+    Whitelisted,
+    /// Only the owner can create pools.
+    OwnerOnly,
+}
+
+/// Configuration of a token allowed in pools.
+#[near(serializers = [json, borsh])]
+#[derive(Clone)]
+pub struct TokenConfig {
+    /// Token decimals.
+    pub decimals: u8,
+    /// Minimum amount of a single deposit.
+    pub min_deposit: U128,
+}
 
-const ERR_POOL_NOT_FOUND: &str = "Pool not found";
-const ERR_BAD_TOKEN_ID: &str = "Token doesn't exist in pool";
-const ERR_INVALID_AMOUNT: &str = "Amount must be > 0";
+/// Fee change scheduled by the pool operator
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingPoolFee {
+    /// New fee in basis points
+    pub fee: u32,
+    /// Time after which the new fee can be applied
+    pub effective_at_ms: TimestampMs,
+}
+
+/// How a pool accepts and values deposits
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DepositMode {
+    /// Deposits must contain every pool token and match the pool's token ratio, within the
+    /// tolerance in basis points of the matching amount. The unused part is refunded.
+    Balanced { tolerance_bps: u32 },
+    /// Any token can be deposited on its own and is valued at the pool's token ratio.
+    SingleSided,
+}
+
+/// Deposit limits of a token in a pool. `None` means no limit.
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TokenLimits {
+    /// Maximum amount of the token held by the pool
+    pub max_pool_amount: Option<U128>,
+    /// Maximum amount of the token deposited by a single account
+    pub max_account_amount: Option<U128>,
+    /// Minimum amount of a single deposit, on top of the minimum of the token allowlist
+    pub min_deposit: Option<U128>,
+}
+
+impl TokenLimits {
+    pub fn assert_valid(&self) {
+        if let (Some(min_deposit), Some(max_account_amount)) =
+            (self.min_deposit, self.max_account_amount)
+        {
+            require!(
+                min_deposit.0 <= max_account_amount.0,
+                "Minimum deposit exceeds the account limit"
+            );
+        }
+        if let (Some(max_account_amount), Some(max_pool_amount)) =
+            (self.max_account_amount, self.max_pool_amount)
+        {
+            require!(
+                max_account_amount.0 <= max_pool_amount.0,
+                "Account limit exceeds the pool limit"
+            );
+        }
+    }
+}
+
+/// Deposits of a liquidity provider into a pool
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Debug)]
+pub struct PoolDeposit {
+    /// Total amount deposited per token, in the order of the pool's `token_ids`
+    pub amounts: Vec<U128>,
+    /// Time of the last deposit
+    pub last_deposit_at_ms: TimestampMs,
+}
+
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolStatus {
+    /// The pool account is being created. The pool ID is reserved.
+    Pending,
+    /// The pool is accepting deposits and worker registrations.
+    Active,
+    /// The pool account couldn't be created.
+    Failed,
+    /// The pool is no longer accepting deposits, but its worker keeps running.
+    DepositsClosed,
+    /// The worker key is revoked, deposits and workers are rejected.
+    WindingDown,
+    /// The pool is closed for good.
+    Closed,
+}
 
 #[near(serializers = [borsh])]
 pub struct Pool {
     /// List of tokens in the pool.
     pub token_ids: Vec<AccountId>,
-    /// How much NEAR this contract has.
+    /// Amount of each token deposited into the pool.
     pub amounts: Vec<Balance>,
     /// Fee charged for swap in basis points
     pub fee: u32,
@@ -31,13 +160,38 @@ pub struct Pool {
     pub worker_id: Option<AccountId>,
     /// Last ping timestamp by the pool's worker.
     pub last_ping_timestamp_ms: TimestampMs,
+    /// Approved compose hashes allowed to register for this pool. `None` allows all approved hashes.
+    pub allowed_compose_hashes: Option<Vec<String>>,
+    /// Hash of the code deployed to the pool's vault account. `None` if the vault predates tracking.
+    pub vault_code_hash: Option<String>,
+    /// Pool status.
+    pub status: PoolStatus,
+    /// Account that created the pool. `None` if the pool predates tracking.
+    pub creator_id: Option<AccountId>,
+    /// Account managing the pool. `None` if the pool predates tracking, then only the owner manages it.
+    pub operator_id: Option<AccountId>,
+    /// Worker ping timeout of this pool. `None` uses the registry default.
+    pub worker_ping_timeout_ms: Option<TimestampMs>,
+    /// Fee change scheduled by the operator.
+    pub pending_fee: Option<PendingPoolFee>,
+    /// Deposits by liquidity providers.
+    pub depositors: IterableMap<AccountId, PoolDeposit>,
+    /// Deposit limits per token, in the order of `token_ids`.
+    pub token_limits: Vec<TokenLimits>,
+    /// How deposits are accepted and valued.
+    pub deposit_mode: DepositMode,
+    /// Key of the worker being registered, until the pool vault has added it.
+    pub pending_worker_key: Option<PublicKey>,
+    /// Incremented whenever the worker or the key being registered changes, so that
+    /// reconciliation can tell if the keys it fetched are stale.
+    pub worker_key_nonce: u64,
 }
 
 #[near(serializers = [json])]
 pub struct PoolInfo {
     /// List of tokens in the pool.
     pub token_ids: Vec<AccountId>,
-    /// How much NEAR this contract has.
+    /// Amount of each token deposited into the pool.
     pub amounts: Vec<U128>,
     /// Fee charged for swap in basis points
     pub fee: u32,
@@ -47,61 +201,338 @@ pub struct PoolInfo {
     pub worker_id: Option<AccountId>,
     /// Last ping timestamp by the pool's worker.
     pub last_ping_timestamp_ms: TimestampMs,
+    /// Approved compose hashes allowed to register for this pool. `None` allows all approved hashes.
+    pub allowed_compose_hashes: Option<Vec<String>>,
+    /// Hash of the code deployed to the pool's vault account. `None` if the vault predates tracking.
+    pub vault_code_hash: Option<String>,
+    /// Pool status.
+    pub status: PoolStatus,
+    /// Account that created the pool. `None` if the pool predates tracking.
+    pub creator_id: Option<AccountId>,
+    /// Account managing the pool. `None` if the pool predates tracking, then only the owner manages it.
+    pub operator_id: Option<AccountId>,
+    /// Worker ping timeout of this pool. `None` uses the registry default.
+    pub worker_ping_timeout_ms: Option<TimestampMs>,
+    /// Fee change scheduled by the operator.
+    pub pending_fee: Option<PendingPoolFee>,
+    /// Deposit limits per token, in the order of `token_ids`.
+    pub token_limits: Vec<TokenLimits>,
+    /// How deposits are accepted and valued.
+    pub deposit_mode: DepositMode,
+    /// Key of the worker being registered, until the pool vault has added it.
+    pub pending_worker_key: Option<PublicKey>,
 }
 
 impl Pool {
-    pub fn new(token_ids: Vec<AccountId>, fee: u32) -> Self {
-        require!(token_ids.len() == 2, "Must have exactly 2 tokens");
-        require!(
-            token_ids[0] != token_ids[1],
-            "The two tokens cannot be identical"
-        );
-        require!(fee < 10_000, "Fee must be less than 100%");
+    /// Create a pending pool. It becomes active once its account is created.
+    pub fn new(
+        pool_id: u32,
+        token_ids: Vec<AccountId>,
+        fee: u32,
+        vault_code_hash: String,
+        creator_id: AccountId,
+        allowed_tokens: &IterableMap<AccountId, TokenConfig>,
+    ) -> Self {
+        Self::assert_valid_params(&token_ids, fee, allowed_tokens);
 
         Self {
             token_ids: token_ids.clone(),
             amounts: vec![0; token_ids.len()],
             fee,
-            shares: LookupMap::new(Prefix::PoolShares),
+            shares: LookupMap::new(Prefix::PoolLpShares { pool_id }),
             shares_total_supply: 0,
             worker_id: None,
             last_ping_timestamp_ms: 0,
+            allowed_compose_hashes: None,
+            vault_code_hash: Some(vault_code_hash),
+            status: PoolStatus::Pending,
+            creator_id: Some(creator_id.clone()),
+            operator_id: Some(creator_id),
+            worker_ping_timeout_ms: None,
+            pending_fee: None,
+            depositors: IterableMap::new(Prefix::PoolDepositors { pool_id }),
+            token_limits: vec![TokenLimits::default(); token_ids.len()],
+            deposit_mode: DepositMode::SingleSided,
+            pending_worker_key: None,
+            worker_key_nonce: 0,
         }
     }
 
-    /// Assume the worker is active if there's a ping within the timeout period.
-    pub fn has_active_worker(&self, timeout_ms: TimestampMs) -> bool {
+    pub fn assert_valid_params(
+        token_ids: &[AccountId],
+        fee: u32,
+        allowed_tokens: &IterableMap<AccountId, TokenConfig>,
+    ) {
+        require!(token_ids.len() == 2, "Must have exactly 2 tokens");
+        require!(
+            token_ids[0] != token_ids[1],
+            "The two tokens cannot be identical"
+        );
+        require!(fee < 10_000, "Fee must be less than 100%");
+        for token_id in token_ids {
+            require!(allowed_tokens.contains_key(token_id), ERR_TOKEN_NOT_ALLOWED);
+        }
+    }
+
+    pub fn assert_active(&self) {
+        require!(self.status == PoolStatus::Active, ERR_POOL_NOT_ACTIVE);
+    }
+
+    /// Workers can register while the pool is active or only closed for deposits.
+    pub fn accepts_workers(&self) -> bool {
+        matches!(self.status, PoolStatus::Active | PoolStatus::DepositsClosed)
+    }
+
+    pub fn assert_accepts_workers(&self) {
+        require!(self.accepts_workers(), ERR_POOL_CLOSED);
+    }
+
+    /// Assume the worker is active if there's a ping within the pool's timeout period,
+    /// or `default_timeout_ms` if the pool doesn't set one.
+    pub fn has_active_worker(&self, default_timeout_ms: TimestampMs) -> bool {
+        let timeout_ms = self.worker_ping_timeout_ms.unwrap_or(default_timeout_ms);
         self.worker_id.is_some() && block_timestamp_ms() < self.last_ping_timestamp_ms + timeout_ms
     }
+
+    pub fn token_index(&self, token_id: &AccountId) -> usize {
+        self.token_ids
+            .iter()
+            .position(|id| id == token_id)
+            .expect(ERR_BAD_TOKEN_ID)
+    }
+
+    /// Amount of a token the pool can still accept, from the account if given.
+    /// `None` if there's no limit.
+    pub fn remaining_capacity(
+        &self,
+        token_index: usize,
+        account_id: Option<&AccountId>,
+    ) -> Option<Balance> {
+        let limits = &self.token_limits[token_index];
+        let pool_capacity = limits
+            .max_pool_amount
+            .map(|max| max.0.saturating_sub(self.amounts[token_index]));
+        let account_capacity = account_id.and(limits.max_account_amount).map(|max| {
+            let deposited = account_id
+                .and_then(|account_id| self.depositors.get(account_id))
+                .map_or(0, |deposit| deposit.amounts[token_index].0);
+            max.0.saturating_sub(deposited)
+        });
+        match (pool_capacity, account_capacity) {
+            (Some(pool), Some(account)) => Some(pool.min(account)),
+            (pool, account) => pool.or(account),
+        }
+    }
+
+    /// The pool holds all of its tokens, so they can be valued at the pool's token ratio
+    pub fn is_seeded(&self) -> bool {
+        self.shares_total_supply > 0 && self.amounts.iter().all(|amount| *amount > 0)
+    }
+
+    /// Amounts of each token the pool accepts from a deposit under its deposit mode
+    pub fn accepted_deposit(&self, deposit: &[Balance]) -> Vec<Balance> {
+        let DepositMode::Balanced { tolerance_bps } = self.deposit_mode else {
+            return deposit.to_vec();
+        };
+        if !self.is_seeded() {
+            // The first deposits set the ratio
+            return deposit.to_vec();
+        }
+        if deposit.iter().any(|amount| *amount == 0) {
+            // A balanced deposit must contain every pool token
+            return vec![0; deposit.len()];
+        }
+
+        // The token deposited the least relative to the pool limits the others
+        let limiting = (0..deposit.len())
+            .min_by(|&i, &j| mul_div(deposit[i], self.amounts[j], self.amounts[i]).cmp(&deposit[j]))
+            .unwrap_or_default();
+        deposit
+            .iter()
+            .zip(&self.amounts)
+            .map(|(amount, pool_amount)| {
+                let matching = mul_div(deposit[limiting], *pool_amount, self.amounts[limiting]);
+                let tolerance = mul_div(matching, tolerance_bps.into(), 10_000);
+                (*amount).min(matching.saturating_add(tolerance))
+            })
+            .collect()
+    }
+
+    /// Shares worth each of the deposited amounts. Each token in the pool holds an equal part
+    /// of the pool value, so a deposit is valued at the pool's token ratio. The first deposit
+    /// mints the initial supply, split evenly between its tokens.
+    pub fn deposit_shares(&self, deposit: &[Balance]) -> Vec<Balance> {
+        if self.shares_total_supply == 0 {
+            let tokens = deposit.iter().filter(|amount| **amount > 0).count().max(1) as Balance;
+            let mut remainder = INIT_SHARES_SUPPLY % tokens;
+            return deposit
+                .iter()
+                .map(|amount| match amount {
+                    0 => 0,
+                    _ => INIT_SHARES_SUPPLY / tokens + std::mem::take(&mut remainder),
+                })
+                .collect();
+        }
+
+        let sides = self
+            .amounts
+            .iter()
+            .filter(|amount| **amount > 0)
+            .count()
+            .max(1) as Balance;
+        deposit
+            .iter()
+            .zip(&self.amounts)
+            .map(|(amount, pool_amount)| match (*amount, *pool_amount) {
+                (0, _) => 0,
+                // A token the pool doesn't hold yet can't be valued against the reserves.
+                // Only the holder of all shares can deposit it, so it's worth as much as
+                // each of the others without diluting anyone else.
+                (_, 0) => self.shares_total_supply / sides,
+                (amount, pool_amount) => {
+                    mul_div(self.shares_total_supply, amount, pool_amount) / sides
+                }
+            })
+            .collect()
+    }
+
+    /// Credit shares reserved by a deposit. They're already part of the total supply.
+    pub fn mint_shares(&mut self, account_id: &AccountId, shares: Balance) {
+        *self.shares.entry(account_id.clone()).or_insert(0) += shares;
+    }
+
+    /// Release shares reserved by a deposit that weren't minted
+    pub fn release_shares(&mut self, shares: Balance) {
+        self.shares_total_supply -= shares;
+    }
+
+    /// Record a deposit by a liquidity provider
+    pub fn record_deposit(&mut self, account_id: &AccountId, token_index: usize, amount: Balance) {
+        let token_count = self.token_ids.len();
+        let deposit = self
+            .depositors
+            .entry(account_id.clone())
+            .or_insert_with(|| PoolDeposit {
+                amounts: vec![U128(0); token_count],
+                last_deposit_at_ms: 0,
+            });
+        deposit.amounts[token_index].0 += amount;
+        deposit.last_deposit_at_ms = block_timestamp_ms();
+    }
+
+    /// Release the capacity reserved for the unused part of a deposit
+    pub fn release_deposit(&mut self, account_id: &AccountId, token_index: usize, amount: Balance) {
+        if amount == 0 {
+            return;
+        }
+        self.amounts[token_index] -= amount;
+        let deposit = self
+            .depositors
+            .get_mut(account_id)
+            .expect("Deposit not found");
+        deposit.amounts[token_index].0 -= amount;
+        if deposit.amounts.iter().all(|amount| amount.0 == 0) {
+            self.depositors.remove(account_id);
+        }
+    }
+
+    /// The pool vault was deployed with v0.1.0, which can't be upgraded
+    pub fn has_legacy_vault(&self) -> bool {
+        self.vault_code_hash.is_none()
+    }
+
+    pub fn is_operator(&self, account_id: &AccountId) -> bool {
+        self.operator_id.as_ref() == Some(account_id)
+    }
+
+    pub fn allows_compose_hash(&self, compose_hash: &String) -> bool {
+        self.allowed_compose_hashes
+            .as_ref()
+            .is_none_or(|hashes| hashes.contains(compose_hash))
+    }
 }
 
 #[near]
 impl Contract {
-    /// Create a new liquidity pool for the given NEP-141 token IDs with fee in basis points
+    /// Create a new liquidity pool for the given NEP-141 token IDs with fee in basis points.
+    /// The caller must be allowed by the pool creation policy and both tokens must be allowed.
+    /// The deposit beyond the pool account storage is refunded, and so is the whole deposit
+    /// if the pool account can't be created.
     #[payable]
     pub fn create_liquidity_pool(
         &mut self,
         token_ids: Vec<AccountId>,
         fee: u32,
     ) -> PromiseOrValue<Option<u32>> {
+        let creator_id = env::predecessor_account_id();
+        self.assert_can_create_pool(&creator_id);
+        Pool::assert_valid_params(&token_ids, fee, &self.allowed_tokens);
+        let storage_deposit = self.create_pool_storage_deposit();
+        let attached_deposit = env::attached_deposit();
         require!(
-            env::attached_deposit() >= CREATE_POOL_STORAGE_DEPOSIT,
+            attached_deposit >= storage_deposit,
             "Not enough attached deposit"
         );
 
-        // Get new pool ID
+        let excess_deposit = attached_deposit.saturating_sub(storage_deposit);
+        if !excess_deposit.is_zero() {
+            Promise::new(creator_id.clone()).transfer(excess_deposit);
+
+            Event::Refunded {
+                receiver_id: &creator_id,
+                token_id: &None,
+                amount: &U128(excess_deposit.as_yoctonear()),
+                reason: "Excess pool storage deposit",
+            }
+            .emit();
+        }
+
+        // Reserve the pool ID now, so that concurrent creations get different IDs
         let pool_id = self.pools.len();
 
-        // Create sub account for managing liquidity pool's assets in NEAR Intents
-        let pool_account_id = self.get_pool_account_id(pool_id);
-        Promise::new(pool_account_id)
+        // Create sub account for managing liquidity pool's assets in NEAR Intents.
+        // Reference the vault global contract if it's published, otherwise deploy a full copy.
+        let create_account = Promise::new(self.get_pool_account_id(pool_id))
             .create_account()
-            .transfer(CREATE_POOL_STORAGE_DEPOSIT)
-            .deploy_contract(include_bytes!("../../intents-vault/res/intents_vault.wasm").to_vec())
+            .transfer(storage_deposit);
+        let (create_account, vault_code_hash) = match self.vault_global_code_hash {
+            Some(code_hash) => (
+                create_account.use_global_contract(code_hash.to_vec()),
+                hex::encode(code_hash),
+            ),
+            None => {
+                let vault_code = self.vault_code();
+                let vault_code_hash = hex::encode(env::sha256(&vault_code));
+                (create_account.deploy_contract(vault_code), vault_code_hash)
+            }
+        };
+        // The vault records the registry as the caller of its init
+        let create_account = create_account.function_call(
+            "new".to_string(),
+            serde_json::to_vec(
+                &serde_json::json!({ "intents_contract_id": self.intents_contract_id }),
+            )
+            .unwrap(),
+            NearToken::from_yoctonear(0),
+            GAS_INIT_POOL_VAULT,
+        );
+
+        let pool = Pool::new(
+            pool_id,
+            token_ids,
+            fee,
+            vault_code_hash,
+            creator_id.clone(),
+            &self.allowed_tokens,
+        );
+        self.pools.push(pool);
+        self.pools.flush();
+
+        create_account
             .then(
                 Self::ext(env::current_account_id())
                     .with_static_gas(GAS_CREATE_POOL_CALLBACK)
-                    .on_create_liquidity_pool_account(pool_id, token_ids, fee),
+                    .on_create_liquidity_pool_account(pool_id, creator_id, storage_deposit),
             )
             .into()
     }
@@ -110,22 +541,37 @@ impl Contract {
     pub fn on_create_liquidity_pool_account(
         &mut self,
         pool_id: u32,
-        token_ids: Vec<AccountId>,
-        fee: u32,
+        creator_id: AccountId,
+        storage_deposit: NearToken,
         #[callback_result] call_result: Result<(), PromiseError>,
     ) -> Option<u32> {
+        let pool = self.pools.get_mut(pool_id).expect(ERR_POOL_NOT_FOUND);
         if call_result.is_err() {
+            pool.status = PoolStatus::Failed;
+            self.pools.flush();
+
+            // The deposit transferred to the pool account comes back to the registry
+            Promise::new(creator_id.clone()).transfer(storage_deposit);
+
+            Event::CreateLiquidityPoolFailed {
+                pool_id: &pool_id,
+                creator_id: &creator_id,
+                refund: &storage_deposit,
+            }
+            .emit();
+
             None
         } else {
-            // Add the new liquidity pool
-            let pool = Pool::new(token_ids.clone(), fee);
-            self.pools.push(pool);
+            pool.status = PoolStatus::Active;
+            let token_ids = pool.token_ids.clone();
+            let fee = pool.fee;
             self.pools.flush();
 
             Event::CreateLiquidityPool {
                 pool_id: &pool_id,
                 token_ids: &token_ids,
                 fee: &fee,
+                creator_id: &creator_id,
             }
             .emit();
 
@@ -133,19 +579,188 @@ impl Contract {
         }
     }
 
+    /// Stop accepting deposits into an active pool. Callable by the owner or the pool operator.
+    pub fn close_pool_deposits(&mut self, pool_id: u32) {
+        self.set_pool_status(pool_id, &[PoolStatus::Active], PoolStatus::DepositsClosed);
+    }
+
+    /// Accept deposits again. Callable by the owner or the pool operator.
+    pub fn reopen_pool_deposits(&mut self, pool_id: u32) {
+        self.set_pool_status(pool_id, &[PoolStatus::DepositsClosed], PoolStatus::Active);
+    }
+
+    /// Start winding down the pool. The worker key is revoked from the pool vault, so that
+    /// the pool stops trading. Callable by the owner or the pool operator.
+    /// There's no withdrawal of liquidity yet: the pool's balances stay in its vault.
+    #[payable]
+    pub fn wind_down_pool(&mut self, pool_id: u32) -> PromiseOrValue<()> {
+        assert_one_yocto();
+        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        self.assert_pool_operator(pool);
+        require!(
+            matches!(pool.status, PoolStatus::Active | PoolStatus::DepositsClosed),
+            ERR_INVALID_STATUS_TRANSITION
+        );
+
+        let Some(worker_id) = pool.worker_id.clone() else {
+            self.set_pool_status(
+                pool_id,
+                &[PoolStatus::Active, PoolStatus::DepositsClosed],
+                PoolStatus::WindingDown,
+            );
+            return PromiseOrValue::Value(());
+        };
+        let worker = self
+            .worker_by_account_id
+            .get(&worker_id)
+            .expect("Worker not registered");
+
+        self.remove_vault_public_key(pool_id, worker.public_key.clone(), GAS_REVOKE_WORKER_KEY)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_REVOKE_WORKER_KEY_CALLBACK)
+                    .on_pool_worker_key_revoked(pool_id, worker_id),
+            )
+            .into()
+    }
+
+    /// A worker registered while the key was being revoked has its key revoked too.
+    /// Registrations still in flight remove their key once they see the pool winding down.
+    #[private]
+    pub fn on_pool_worker_key_revoked(
+        &mut self,
+        pool_id: u32,
+        worker_id: AccountId,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) -> PromiseOrValue<()> {
+        if call_result.is_err() {
+            env::panic_str("Failed to revoke worker key");
+        }
+
+        // The worker may have been replaced while its key was being revoked
+        let pool = self.pools.get_mut(pool_id).expect(ERR_POOL_NOT_FOUND);
+        if pool.worker_id.as_ref() == Some(&worker_id) {
+            pool.worker_id = None;
+            pool.worker_key_nonce += 1;
+            let worker = self
+                .worker_by_account_id
+                .remove(&worker_id)
+                .expect("Worker not registered");
+            Event::WorkerRemoved {
+                worker_id: &worker_id,
+                pool_id: &pool_id,
+                public_key: &worker.public_key,
+                compose_hash: &worker.compose_hash,
+                checksum: &worker.checksum,
+            }
+            .emit();
+        }
+
+        let pool = self.pools.get_mut(pool_id).expect(ERR_POOL_NOT_FOUND);
+        let old_status = pool.status;
+        let replacement_worker_id = pool.worker_id.clone();
+        if old_status != PoolStatus::WindingDown {
+            pool.status = PoolStatus::WindingDown;
+            Event::PoolStatusChanged {
+                pool_id: &pool_id,
+                old_status: &old_status,
+                new_status: &PoolStatus::WindingDown,
+            }
+            .emit();
+        }
+        self.pools.flush();
+
+        let Some(replacement_worker_id) = replacement_worker_id else {
+            return PromiseOrValue::Value(());
+        };
+        let replacement_worker = self
+            .worker_by_account_id
+            .get(&replacement_worker_id)
+            .expect("Worker not registered");
+        self.remove_vault_public_key(
+            pool_id,
+            replacement_worker.public_key.clone(),
+            GAS_REVOKE_WORKER_KEY,
+        )
+        .then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_REVOKE_WORKER_KEY_CALLBACK)
+                .on_pool_worker_key_revoked(pool_id, replacement_worker_id),
+        )
+        .into()
+    }
+
+    /// Close a pool that is winding down. Callable by the owner or the pool operator.
+    /// Neither the vault balances nor the outstanding shares are checked, they're left as is.
+    pub fn close_pool(&mut self, pool_id: u32) {
+        self.set_pool_status(pool_id, &[PoolStatus::WindingDown], PoolStatus::Closed);
+    }
+
+    /// Set the deposit limits of a pool token. Callable by the owner or the pool operator.
+    pub fn set_pool_token_limits(
+        &mut self,
+        pool_id: u32,
+        token_id: AccountId,
+        limits: TokenLimits,
+    ) {
+        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        self.assert_pool_operator(pool);
+        let token_index = pool.token_index(&token_id);
+        limits.assert_valid();
+
+        let pool = self.pools.get_mut(pool_id).expect(ERR_POOL_NOT_FOUND);
+        pool.token_limits[token_index] = limits.clone();
+        self.pools.flush();
+
+        Event::PoolTokenLimitsUpdated {
+            pool_id: &pool_id,
+            token_id: &token_id,
+            limits: &limits,
+        }
+        .emit();
+    }
+
+    /// `amount` was received from the sender, of which `deposit_amount` was forwarded to the pool
+    /// for `shares`.
     #[private]
+    #[allow(clippy::too_many_arguments)]
     pub fn on_deposit_into_pool(
         &mut self,
+        pool_id: u32,
+        token_id: AccountId,
+        sender_id: AccountId,
         amount: U128,
+        deposit_amount: U128,
+        shares: U128,
         #[callback_result] used_fund: Result<U128, PromiseError>,
     ) -> U128 {
-        if let Ok(used_fund) = used_fund {
-            // Refund the unused amount.
-            // ft_transfser_call() returns the used fund
-            U128(amount.0.saturating_sub(used_fund.0))
-        } else {
-            amount
+        // Refund the unused amount.
+        // ft_transfser_call() returns the used fund
+        let used_fund = used_fund.map_or(0, |used_fund| used_fund.0.min(deposit_amount.0));
+        let refund = U128(amount.0 - used_fund);
+
+        let pool = self.pools.get_mut(pool_id).expect(ERR_POOL_NOT_FOUND);
+        let token_index = pool.token_index(&token_id);
+        pool.release_deposit(&sender_id, token_index, deposit_amount.0 - used_fund);
+        // Mint the shares for the used amount
+        let minted = U128(mul_div(shares.0, used_fund, deposit_amount.0));
+        pool.release_shares(shares.0 - minted.0);
+        if minted.0 > 0 {
+            pool.mint_shares(&sender_id, minted.0);
+        }
+        self.pools.flush();
+
+        Event::DepositIntoPool {
+            pool_id: &pool_id,
+            sender_id: &sender_id,
+            token_id: &token_id,
+            amount: &amount,
+            refund: &refund,
+            shares: &minted,
         }
+        .emit();
+
+        refund
     }
 }
 
@@ -156,17 +771,202 @@ impl Contract {
             .unwrap()
     }
 
-    pub(crate) fn deposit_into_pool(
+    /// Authorize a key through the pool vault, with the interface of its version
+    pub(crate) fn add_vault_public_key(
         &self,
         pool_id: u32,
+        public_key: PublicKey,
+        gas: Gas,
+    ) -> Promise {
+        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        let pool_account_id = self.get_pool_account_id(pool_id);
+        if pool.has_legacy_vault() {
+            ext_legacy_intents_vault::ext(pool_account_id)
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(gas)
+                .with_unused_gas_weight(0)
+                .add_public_key(self.intents_contract_id.clone(), public_key)
+        } else {
+            ext_intents_vault::ext(pool_account_id)
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(gas)
+                .with_unused_gas_weight(0)
+                .add_public_key(public_key)
+        }
+    }
+
+    /// Revoke a key through the pool vault, with the interface of its version
+    pub(crate) fn remove_vault_public_key(
+        &self,
+        pool_id: u32,
+        public_key: PublicKey,
+        gas: Gas,
+    ) -> Promise {
+        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        let pool_account_id = self.get_pool_account_id(pool_id);
+        if pool.has_legacy_vault() {
+            ext_legacy_intents_vault::ext(pool_account_id)
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(gas)
+                .with_unused_gas_weight(0)
+                .remove_public_key(self.intents_contract_id.clone(), public_key)
+        } else {
+            ext_intents_vault::ext(pool_account_id)
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(gas)
+                .with_unused_gas_weight(0)
+                .remove_public_key(public_key)
+        }
+    }
+
+    pub(crate) fn assert_can_create_pool(&self, account_id: &AccountId) {
+        let allowed = match self.pool_creation_policy {
+            PoolCreationPolicy::Open => true,
+            PoolCreationPolicy::Whitelisted => {
+                account_id == &self.owner_id || self.pool_creators.contains(account_id)
+            }
+            PoolCreationPolicy::OwnerOnly => account_id == &self.owner_id,
+        };
+        require!(allowed, "Not allowed to create pools");
+    }
+
+    fn set_pool_status(&mut self, pool_id: u32, from: &[PoolStatus], to: PoolStatus) {
+        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        self.assert_pool_operator(pool);
+        let old_status = pool.status;
+        require!(from.contains(&old_status), ERR_INVALID_STATUS_TRANSITION);
+
+        let pool = self.pools.get_mut(pool_id).expect(ERR_POOL_NOT_FOUND);
+        pool.status = to;
+        self.pools.flush();
+
+        Event::PoolStatusChanged {
+            pool_id: &pool_id,
+            old_status: &old_status,
+            new_status: &to,
+        }
+        .emit();
+    }
+
+    pub(crate) fn create_pool_storage_deposit(&self) -> NearToken {
+        if self.vault_global_code_hash.is_some() {
+            CREATE_POOL_GLOBAL_STORAGE_DEPOSIT
+        } else {
+            CREATE_POOL_STORAGE_DEPOSIT
+        }
+    }
+
+    /// Vault code for new pools and vault upgrades, unless a global contract is published
+    pub(crate) fn vault_code(&self) -> Vec<u8> {
+        self.staged_vault_code
+            .get()
+            .clone()
+            .unwrap_or_else(|| INTENTS_VAULT_WASM.to_vec())
+    }
+
+    /// Hex encoded hash of the vault code new pools and vault upgrades use
+    pub(crate) fn vault_code_hash(&self) -> String {
+        match self.vault_global_code_hash {
+            Some(code_hash) => hex::encode(code_hash),
+            None => hex::encode(env::sha256(&self.vault_code())),
+        }
+    }
+
+    /// Accept as much of a deposit as the pool limits and deposit mode allow, and reserve it
+    /// in the pool. Returns the accepted amounts and the shares each of them is worth.
+    pub(crate) fn reserve_deposit(
+        &mut self,
+        pool_id: u32,
+        sender_id: &AccountId,
+        deposit: &[Balance],
+    ) -> (Vec<Balance>, Vec<Balance>) {
+        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        pool.assert_active();
+
+        // Anyone can make the first deposit of all tokens, which sets the pool ratio
+        let is_first_deposit = pool.shares_total_supply == 0
+            && pool.amounts.iter().all(|amount| *amount == 0)
+            && deposit.iter().all(|amount| *amount > 0);
+        require!(
+            pool.is_seeded() || is_first_deposit || self.is_pool_operator(pool, sender_id),
+            ERR_POOL_NOT_SEEDED
+        );
+
+        let mut capped_deposit = Vec::with_capacity(deposit.len());
+        for (token_index, (token_id, amount)) in pool.token_ids.iter().zip(deposit).enumerate() {
+            if *amount == 0 {
+                capped_deposit.push(0);
+                continue;
+            }
+            let min_deposit = self
+                .allowed_tokens
+                .get(token_id)
+                .map_or(0, |config| config.min_deposit.0)
+                .max(
+                    pool.token_limits[token_index]
+                        .min_deposit
+                        .map_or(0, |min| min.0),
+                );
+            require!(*amount >= min_deposit, ERR_DEPOSIT_TOO_SMALL);
+
+            let capped_amount = pool
+                .remaining_capacity(token_index, Some(sender_id))
+                .map_or(*amount, |capacity| capacity.min(*amount));
+            require!(capped_amount > 0, ERR_DEPOSIT_CAP_REACHED);
+            capped_deposit.push(capped_amount);
+        }
+
+        let accepted = pool.accepted_deposit(&capped_deposit);
+        require!(
+            deposit
+                .iter()
+                .zip(&accepted)
+                .all(|(amount, accepted)| *amount == 0 || *accepted > 0),
+            ERR_DEPOSIT_RATIO
+        );
+        let adds_token = pool.shares_total_supply > 0
+            && accepted
+                .iter()
+                .zip(&pool.amounts)
+                .any(|(amount, pool_amount)| *amount > 0 && *pool_amount == 0);
+        require!(
+            !adds_token || pool.shares.get(sender_id) == Some(&pool.shares_total_supply),
+            ERR_UNSEEDED_TOKEN
+        );
+        let shares = pool.deposit_shares(&accepted);
+
+        // Reserve the pool and account capacity and the shares, so that concurrent deposits
+        // can't exceed the limits or mint the initial supply twice. Callbacks release the part
+        // that isn't used.
+        let pool = self.pools.get_mut(pool_id).expect(ERR_POOL_NOT_FOUND);
+        for (token_index, amount) in accepted.iter().enumerate() {
+            if *amount > 0 {
+                pool.amounts[token_index] += amount;
+                pool.record_deposit(sender_id, token_index, *amount);
+            }
+        }
+        pool.shares_total_supply += shares.iter().sum::<Balance>();
+        self.pools.flush();
+
+        (accepted, shares)
+    }
+
+    pub(crate) fn deposit_into_pool(
+        &mut self,
+        pool_id: u32,
         token_id: &AccountId,
-        _sender_id: &AccountId,
+        sender_id: &AccountId,
         amount: Balance,
     ) -> PromiseOrValue<U128> {
         let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
-
         require!(pool.token_ids.contains(token_id), ERR_BAD_TOKEN_ID);
         require!(amount > 0, ERR_INVALID_AMOUNT);
+        let token_index = pool.token_index(token_id);
+
+        let mut deposit = vec![0; pool.token_ids.len()];
+        deposit[token_index] = amount;
+        let (accepted, shares) = self.reserve_deposit(pool_id, sender_id, &deposit);
+        let deposit_amount = accepted[token_index];
 
         // deposit the fund into NEAR Intents
         // NEAR Intents docs: https://docs.near-intents.org/near-intents/market-makers/verifier/deposits-and-withdrawals/deposits
@@ -174,11 +974,18 @@ impl Contract {
             .with_attached_deposit(NearToken::from_yoctonear(1))
             .ft_transfer_call(
                 self.intents_contract_id.clone(),
-                U128(amount),
+                U128(deposit_amount),
                 Some("deposit into pool".to_string()),
                 self.get_pool_account_id(pool_id).to_string(),
             )
-            .then(Self::ext(env::current_account_id()).on_deposit_into_pool(U128(amount)))
+            .then(Self::ext(env::current_account_id()).on_deposit_into_pool(
+                pool_id,
+                token_id.clone(),
+                sender_id.clone(),
+                U128(amount),
+                U128(deposit_amount),
+                U128(shares[token_index]),
+            ))
             .into()
     }
 }
@@ -0,0 +1,151 @@
+use near_sdk::{assert_one_yocto, near, require, AccountId, Gas, PromiseOrValue, PromiseResult};
+use std::collections::HashSet;
+
+use crate::events::Event;
+use crate::ext::ext_intents;
+use crate::pool::ERR_POOL_NOT_FOUND;
+use crate::*;
+
+const GAS_GET_POOL_KEYS: Gas = Gas::from_tgas(5);
+const GAS_POOL_KEYS_CALLBACK: Gas = Gas::from_tgas(10);
+const GAS_REMOVE_ORPHAN_KEY: Gas = Gas::from_tgas(20);
+const GAS_ORPHAN_KEYS_CALLBACK: Gas = Gas::from_tgas(10);
+/// Maximum number of orphan keys removed by a single reconciliation
+const MAX_ORPHAN_KEYS: usize = 5;
+
+#[near]
+impl Contract {
+    /// Reconcile the keys of the pool account on NEAR Intents with the registered worker.
+    /// Keys that don't belong to the registered worker, or to a worker being registered,
+    /// are removed through the pool vault, and the worker is removed if its key is no longer
+    /// on NEAR Intents.
+    /// Emits `PoolKeysReconciled` with the outcome.
+    #[payable]
+    pub fn reconcile_pool_keys(&mut self, pool_id: u32) -> Promise {
+        assert_one_yocto();
+        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        self.assert_pool_operator(pool);
+        let worker_key_nonce = pool.worker_key_nonce;
+
+        ext_intents::ext(self.intents_contract_id.clone())
+            .with_static_gas(GAS_GET_POOL_KEYS)
+            .with_unused_gas_weight(0)
+            .public_keys_of(self.get_pool_account_id(pool_id))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_POOL_KEYS_CALLBACK)
+                    .on_pool_keys_fetched(pool_id, worker_key_nonce),
+            )
+    }
+
+    #[private]
+    pub fn on_pool_keys_fetched(
+        &mut self,
+        pool_id: u32,
+        worker_key_nonce: u64,
+        #[callback_result] public_keys: Result<HashSet<PublicKey>, PromiseError>,
+    ) -> PromiseOrValue<()> {
+        let public_keys = public_keys.expect("Failed to fetch pool keys");
+        let pool = self.pools.get(pool_id).expect(ERR_POOL_NOT_FOUND);
+        // The fetched keys are stale if a worker was registered or removed in the meantime
+        require!(
+            pool.worker_key_nonce == worker_key_nonce,
+            "Pool worker changed during the reconciliation"
+        );
+        let worker_id = pool.worker_id.clone();
+        // The key of a worker being registered isn't an orphan
+        let pending_key = pool.pending_worker_key.clone();
+        let mut registered_key = worker_id
+            .as_ref()
+            .and_then(|worker_id| self.worker_by_account_id.get(worker_id))
+            .map(|worker| worker.public_key.clone());
+
+        // The worker can't operate the pool without its key
+        let mut removed_worker_id = None;
+        if let (Some(worker_id), Some(public_key)) = (worker_id, registered_key.as_ref()) {
+            if !public_keys.contains(public_key) {
+                self.remove_pool_worker(pool_id, &worker_id);
+                removed_worker_id = Some(worker_id);
+                registered_key = None;
+            }
+        }
+
+        let orphan_keys: Vec<PublicKey> = public_keys
+            .into_iter()
+            .filter(|public_key| {
+                Some(public_key) != registered_key.as_ref()
+                    && Some(public_key) != pending_key.as_ref()
+            })
+            .take(MAX_ORPHAN_KEYS)
+            .collect();
+
+        let Some(removals) = orphan_keys
+            .iter()
+            .map(|public_key| {
+                self.remove_vault_public_key(pool_id, public_key.clone(), GAS_REMOVE_ORPHAN_KEY)
+            })
+            .reduce(|removals, removal| removals.and(removal))
+        else {
+            Event::PoolKeysReconciled {
+                pool_id: &pool_id,
+                removed_keys: &vec![],
+                failed_keys: &vec![],
+                removed_worker_id: &removed_worker_id,
+            }
+            .emit();
+            return PromiseOrValue::Value(());
+        };
+
+        removals
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_ORPHAN_KEYS_CALLBACK)
+                    .with_unused_gas_weight(0)
+                    .on_orphan_keys_removed(pool_id, orphan_keys, removed_worker_id),
+            )
+            .into()
+    }
+
+    #[private]
+    pub fn on_orphan_keys_removed(
+        &self,
+        pool_id: u32,
+        orphan_keys: Vec<PublicKey>,
+        removed_worker_id: Option<AccountId>,
+    ) {
+        let (removed_keys, failed_keys): (Vec<_>, Vec<_>) =
+            orphan_keys.into_iter().enumerate().partition(|(i, _)| {
+                matches!(env::promise_result(*i as u64), PromiseResult::Successful(_))
+            });
+
+        Event::PoolKeysReconciled {
+            pool_id: &pool_id,
+            removed_keys: &removed_keys.into_iter().map(|(_, key)| key).collect(),
+            failed_keys: &failed_keys.into_iter().map(|(_, key)| key).collect(),
+            removed_worker_id: &removed_worker_id,
+        }
+        .emit();
+    }
+}
+
+impl Contract {
+    fn remove_pool_worker(&mut self, pool_id: u32, worker_id: &AccountId) {
+        let worker = self
+            .worker_by_account_id
+            .remove(worker_id)
+            .expect("Worker not registered");
+        let pool = self.pools.get_mut(pool_id).expect(ERR_POOL_NOT_FOUND);
+        pool.worker_id = None;
+        pool.worker_key_nonce += 1;
+        self.pools.flush();
+
+        Event::WorkerRemoved {
+            worker_id,
+            pool_id: &pool_id,
+            public_key: &worker.public_key,
+            compose_hash: &worker.compose_hash,
+            checksum: &worker.checksum,
+        }
+        .emit();
+    }
+}
@@ -4,10 +4,18 @@ use near_sdk::{near, AccountId, PromiseOrValue};
 use crate::*;
 
 const ERR_MALFORMED_MESSAGE: &str = "Invalid transfer action message";
+const ERR_NOT_INTENTS_CONTRACT: &str = "Only NEAR Intents can transfer multi-tokens";
 
 #[near(serializers=[json])]
 enum TokenReceiverMessage {
-    DepositIntoPool { pool_id: u32 },
+    DepositIntoPool {
+        pool_id: u32,
+    },
+    /// Deposit one of the pool tokens as part of a batch deposit. The tokens are held
+    /// until all pool tokens have arrived.
+    BatchDepositIntoPool {
+        pool_id: u32,
+    },
 }
 
 #[near]
@@ -30,6 +38,39 @@ impl Contract {
             TokenReceiverMessage::DepositIntoPool { pool_id } => {
                 self.deposit_into_pool(pool_id, &token_id, &sender_id, amount.0)
             }
+            TokenReceiverMessage::BatchDepositIntoPool { pool_id } => {
+                self.batch_deposit_into_pool(pool_id, &token_id, &sender_id, amount.0)
+            }
+        }
+    }
+
+    /// NEP-245 receiver for token balances held in NEAR Intents. Both deposit messages
+    /// deposit all transferred tokens together, as they arrive at once.
+    #[allow(unused_variables)]
+    pub fn mt_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_ids: Vec<AccountId>,
+        token_ids: Vec<String>,
+        amounts: Vec<U128>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        require!(
+            env::predecessor_account_id() == self.intents_contract_id,
+            ERR_NOT_INTENTS_CONTRACT
+        );
+        if msg.is_empty() {
+            // refund all
+            return PromiseOrValue::Value(amounts);
+        }
+
+        let message =
+            serde_json::from_str::<TokenReceiverMessage>(&msg).expect(ERR_MALFORMED_MESSAGE);
+        match message {
+            TokenReceiverMessage::DepositIntoPool { pool_id }
+            | TokenReceiverMessage::BatchDepositIntoPool { pool_id } => {
+                self.mt_deposit_into_pool(pool_id, &sender_id, token_ids, amounts)
+            }
         }
     }
 }
@@ -1,4 +1,6 @@
-use near_sdk::{near, BorshStorageKey};
+use near_sdk::env::block_timestamp_ms;
+use near_sdk::json_types::U128;
+use near_sdk::{near, AccountId, BorshStorageKey};
 
 pub type Balance = u128;
 pub type TimestampMs = u64;
@@ -6,8 +8,95 @@ pub type TimestampMs = u64;
 #[near]
 #[derive(BorshStorageKey)]
 pub enum Prefix {
+    /// Pools of contracts deployed with v0.1.0, which keep their entries here
     Pools,
+    /// Retired in v0.2.0, shared by all pools and replaced by `PoolLpShares`
     PoolShares,
+    /// Retired in v0.2.0, replaced by `ComposeHashes`
     ApprovedComposeHashes,
+    /// Workers of contracts deployed with v0.1.0, which keep their entries here
     WorkerByAccountId,
+    ComposeHashes,
+    VersionedPools,
+    VersionedWorkers,
+    StagedCode,
+    StagedVaultCode,
+    PoolCreators,
+    AllowedTokens,
+    PoolDepositors {
+        pool_id: u32,
+    },
+    PoolLpShares {
+        pool_id: u32,
+    },
+    PendingDeposits,
+}
+
+/// Which worker pings are logged as `worker_pinged` events
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PingEvents {
+    /// Every ping is logged.
+    All,
+    /// Only pings of a worker that had timed out are logged, as it becomes active again.
+    LivenessChanges,
+}
+
+/// Metadata recorded for an approved docker compose hash.
+#[near(serializers = [json, borsh])]
+#[derive(Clone)]
+pub struct ComposeHashInfo {
+    /// Hex encoded docker compose hash.
+    pub compose_hash: String,
+    /// Human readable label, e.g. the solver strategy name.
+    pub label: Option<String>,
+    /// Source repository of the solver image.
+    pub source_repo: Option<String>,
+    /// Source commit the solver image was built from.
+    pub source_commit: Option<String>,
+    /// Account that approved the compose hash.
+    pub approved_by: AccountId,
+    /// Approval timestamp.
+    pub approved_at_ms: TimestampMs,
+    /// Workers running this compose hash can no longer register or ping after the expiry.
+    pub expires_at_ms: Option<TimestampMs>,
+}
+
+impl ComposeHashInfo {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at_ms
+            .is_some_and(|expires_at_ms| block_timestamp_ms() >= expires_at_ms)
+    }
+}
+
+/// Contract code staged for the next upgrade.
+#[near(serializers = [json, borsh])]
+#[derive(Clone)]
+pub struct StagedCodeInfo {
+    /// Hex encoded sha256 of the staged code.
+    pub code_hash: String,
+    /// Staging timestamp.
+    pub staged_at_ms: TimestampMs,
+    /// Time after which the code can be deployed, fixed by the upgrade delay at staging.
+    pub deployable_at_ms: TimestampMs,
+}
+
+/// Upgrade delay reduction scheduled by the owner
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingUpgradeDelay {
+    /// New upgrade delay
+    pub upgrade_delay_ms: TimestampMs,
+    /// Time after which the new delay can be applied
+    pub effective_at_ms: TimestampMs,
+}
+
+/// Tokens held for a batch deposit until all pool tokens have arrived
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Debug)]
+pub struct PendingDeposit {
+    /// Amount held per token, in the order of the pool's `token_ids`
+    pub amounts: Vec<U128>,
+    /// Time of the first transfer
+    pub created_at_ms: TimestampMs,
 }
@@ -1,10 +1,25 @@
+use crate::events::Event;
+use crate::ext::ext_intents_vault;
+use crate::pool::PoolStatus;
+use crate::types::{PendingUpgradeDelay, StagedCodeInfo, TimestampMs};
+use crate::versioned::{write_state_version, VersionedContract, STATE_VERSION};
 use crate::{Contract, ContractExt};
 
+use near_sdk::env::block_timestamp_ms;
+use near_sdk::json_types::Base58CryptoHash;
 use near_sdk::{
-    assert_one_yocto, env, near_bindgen, AccountId, Gas, GasWeight, NearToken, Promise,
-    PromiseOrValue,
+    assert_one_yocto, env, near_bindgen, require, serde_json, AccountId, CryptoHash, Gas,
+    GasWeight, NearToken, Promise, PromiseError, PromiseOrValue, PromiseResult, PublicKey,
 };
 
+const GAS_UPGRADE_POOL_VAULT: Gas = Gas::from_tgas(30);
+const GAS_UPGRADE_POOL_VAULT_CALLBACK: Gas = Gas::from_tgas(30);
+const GAS_MIGRATE_POOL_VAULT: Gas = Gas::from_tgas(10);
+const GAS_MIGRATE_POOL_VAULT_CALLBACK: Gas = Gas::from_tgas(10);
+const GAS_POOL_VAULTS_UPGRADED_CALLBACK: Gas = Gas::from_tgas(10);
+const GAS_PUBLISH_VAULT_CALLBACK: Gas = Gas::from_tgas(10);
+const GAS_STAGED_CODE_DEPLOYED_CALLBACK: Gas = Gas::from_tgas(10);
+
 #[near_bindgen]
 impl Contract {
     #[init(ignore_state)]
@@ -12,12 +27,55 @@ impl Contract {
     #[private]
     pub fn migrate() -> Self {
         assert_one_yocto();
-        env::state_read::<Self>().expect("Failed to read contract state")
+        let state = VersionedContract::read();
+        let from_state_version = state.version();
+        let contract = Self::from(state);
+        write_state_version();
+
+        Event::ContractMigrated {
+            from_state_version: &from_state_version,
+            to_state_version: &STATE_VERSION,
+        }
+        .emit();
+        contract
     }
 
-    pub fn upgrade(&mut self) -> PromiseOrValue<AccountId> {
+    /// Stage the new contract code passed as the raw input, replacing any previously staged code.
+    /// The code can be deployed with `deploy_staged_code` once the upgrade delay has passed.
+    pub fn stage_code(&mut self) {
         self.assert_owner();
         let code = env::input().expect("Code not found");
+        require!(!code.is_empty(), "Code not found");
+
+        let staged_at_ms = block_timestamp_ms();
+        let info = StagedCodeInfo {
+            code_hash: hex::encode(env::sha256(&code)),
+            staged_at_ms,
+            deployable_at_ms: staged_at_ms + self.upgrade_delay_ms,
+        };
+        self.staged_code.set(Some(code));
+        self.staged_code_info = Some(info.clone());
+
+        Event::CodeStaged {
+            code_hash: &info.code_hash,
+            deployable_at_ms: &info.deployable_at_ms,
+        }
+        .emit();
+    }
+
+    /// Deploy the staged code and migrate the contract state.
+    /// `code_hash` is the hex encoded sha256 of the code the caller expects to deploy.
+    /// The staged code is cleared once the deployment and the migration succeed.
+    pub fn deploy_staged_code(&mut self, code_hash: String) -> Promise {
+        self.assert_owner();
+        let info = self.staged_code_info.as_ref().expect("No staged code");
+        require!(info.code_hash == code_hash, "Staged code hash mismatch");
+        require!(
+            block_timestamp_ms() >= info.deployable_at_ms,
+            "Upgrade delay has not passed"
+        );
+        let code = self.staged_code.get().clone().expect("No staged code");
+
         Promise::new(env::current_account_id())
             .deploy_contract(code)
             .function_call_weight(
@@ -34,6 +92,300 @@ impl Contract {
                 Gas::from_tgas(10),
                 GasWeight(0),
             )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_STAGED_CODE_DEPLOYED_CALLBACK)
+                    .with_unused_gas_weight(0)
+                    .on_staged_code_deployed(code_hash),
+            )
+    }
+
+    #[private]
+    pub fn on_staged_code_deployed(
+        &mut self,
+        code_hash: String,
+        #[callback_result] call_result: Result<AccountId, PromiseError>,
+    ) -> AccountId {
+        let owner_id =
+            call_result.unwrap_or_else(|_| env::panic_str("Failed to deploy the staged code"));
+
+        // Code staged while the deployment was in flight stays staged
+        if self
+            .staged_code_info
+            .as_ref()
+            .is_some_and(|info| info.code_hash == code_hash)
+        {
+            self.staged_code.set(None);
+            self.staged_code_info = None;
+        }
+
+        Event::CodeDeployed {
+            code_hash: &code_hash,
+        }
+        .emit();
+        owner_id
+    }
+
+    /// Stage new vault code passed as the raw input. It replaces the embedded vault code
+    /// for new pools and for `upgrade_pool_vaults`. If a vault global contract is published,
+    /// it's still used until the staged code is published too.
+    pub fn stage_vault_code(&mut self) {
+        self.assert_owner();
+        let code = env::input().expect("Code not found");
+        require!(!code.is_empty(), "Code not found");
+
+        let code_hash = hex::encode(env::sha256(&code));
+        self.staged_vault_code.set(Some(code));
+
+        Event::VaultCodeStaged {
+            code_hash: &code_hash,
+        }
+        .emit();
+    }
+
+    /// Remove the staged vault code and fall back to the embedded vault code
+    pub fn unstage_vault_code(&mut self) {
+        self.assert_owner();
+        self.staged_vault_code.set(None);
+
+        Event::VaultCodeUnstaged {}.emit();
+    }
+
+    /// Publish the current vault code as a global contract identified by its code hash.
+    /// New pool accounts then reference it instead of storing a full copy of the code.
+    /// The registry account pays for the global contract storage.
+    pub fn publish_vault_global_contract(&mut self) -> Promise {
+        self.assert_owner();
+        let code = self.vault_code();
+        let code_hash = env::sha256_array(&code);
+
+        Promise::new(env::current_account_id())
+            .deploy_global_contract(code)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_PUBLISH_VAULT_CALLBACK)
+                    .on_vault_global_contract_published(code_hash.into()),
+            )
+    }
+
+    #[private]
+    pub fn on_vault_global_contract_published(
+        &mut self,
+        code_hash: Base58CryptoHash,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) -> bool {
+        let code_hash: CryptoHash = code_hash.into();
+        if call_result.is_err() {
+            Event::VaultGlobalContractPublishFailed {
+                code_hash: &hex::encode(code_hash),
+            }
+            .emit();
+            return false;
+        }
+
+        self.vault_global_code_hash = Some(code_hash);
+
+        Event::VaultGlobalContractPublished {
+            code_hash: &hex::encode(code_hash),
+        }
+        .emit();
+        true
+    }
+
+    /// Push the current vault code to the vaults of pools in `[from, from + limit)`.
+    /// Once the vault code is published as a global contract, vaults are switched to it by hash,
+    /// so pool accounts funded for the global contract don't need to store the code.
+    /// Pools without a vault account, pools whose vault already runs the current code and
+    /// pools with a v0.1.0 vault, which can't be upgraded, are skipped.
+    /// Each pool needs about 70 Tgas, so keep `limit` small enough for the attached gas.
+    /// The outcome is logged once all vaults of the batch are done, with one `pool_vault_upgraded`
+    /// and one `pool_vault_upgrade_failed` event listing the pools.
+    pub fn upgrade_pool_vaults(&mut self, from: u32, limit: u32) -> u32 {
+        self.assert_owner();
+        let code_hash = self.vault_code_hash();
+        // Read lazily, as the code isn't needed once a global contract is published
+        let mut code = None;
+
+        let to = from.saturating_add(limit).min(self.pools.len());
+        let mut upgrades: Option<Promise> = None;
+        let mut pool_ids = vec![];
+        for pool_id in from..to {
+            let pool = self.pools.get(pool_id).expect("Pool not found");
+            // v0.1.0 vaults have no way to replace their code
+            if matches!(pool.status, PoolStatus::Pending | PoolStatus::Failed)
+                || pool.has_legacy_vault()
+                || pool.vault_code_hash.as_ref() == Some(&code_hash)
+            {
+                continue;
+            }
+
+            let pool_account_id = self.get_pool_account_id(pool_id);
+            let upgrade = match self.vault_global_code_hash {
+                Some(global_code_hash) => ext_intents_vault::ext(pool_account_id)
+                    .with_attached_deposit(NearToken::from_yoctonear(1))
+                    .with_static_gas(GAS_UPGRADE_POOL_VAULT)
+                    .use_global_contract(global_code_hash.into()),
+                None => Promise::new(pool_account_id).function_call(
+                    "upgrade".into(),
+                    code.get_or_insert_with(|| self.vault_code()).clone(),
+                    NearToken::from_yoctonear(1),
+                    GAS_UPGRADE_POOL_VAULT,
+                ),
+            };
+            let upgrade = upgrade.then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_UPGRADE_POOL_VAULT_CALLBACK)
+                    .with_unused_gas_weight(0)
+                    .on_pool_vault_upgraded(pool_id, code_hash.clone()),
+            );
+            upgrades = Some(match upgrades {
+                Some(upgrades) => upgrades.and(upgrade),
+                None => upgrade,
+            });
+            pool_ids.push(pool_id);
+        }
+
+        let num_upgrades = pool_ids.len() as u32;
+        if let Some(upgrades) = upgrades {
+            upgrades.then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_POOL_VAULTS_UPGRADED_CALLBACK)
+                    .with_unused_gas_weight(0)
+                    .on_pool_vaults_upgraded(pool_ids, code_hash),
+            );
+        }
+        num_upgrades
+    }
+
+    /// Emit the outcome of the vault upgrades of a batch, with one log line per kind of event
+    #[private]
+    pub fn on_pool_vaults_upgraded(&self, pool_ids: Vec<u32>, code_hash: String) {
+        let (upgraded, failed): (Vec<_>, Vec<_>) =
+            pool_ids.into_iter().enumerate().partition(|(i, _)| {
+                match env::promise_result(*i as u64) {
+                    PromiseResult::Successful(value) => {
+                        serde_json::from_slice::<bool>(&value).unwrap_or(false)
+                    }
+                    PromiseResult::Failed => false,
+                }
+            });
+
+        let events: Vec<Event> = upgraded
+            .iter()
+            .map(|(_, pool_id)| Event::PoolVaultUpgraded {
+                pool_id,
+                code_hash: &code_hash,
+            })
+            .chain(
+                failed
+                    .iter()
+                    .map(|(_, pool_id)| Event::PoolVaultUpgradeFailed {
+                        pool_id,
+                        code_hash: &code_hash,
+                    }),
+            )
+            .collect();
+        Event::emit_all(&events);
+    }
+
+    #[private]
+    pub fn on_pool_vault_upgraded(
+        &mut self,
+        pool_id: u32,
+        code_hash: String,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) -> PromiseOrValue<bool> {
+        if call_result.is_err() {
+            return PromiseOrValue::Value(false);
+        }
+
+        // Vaults created before the registry and NEAR Intents account IDs were stored
+        // get them from the migration, and those created before keys were tracked start
+        // tracking the registered worker key
+        let pool = self.pools.get(pool_id).expect("Pool not found");
+        let public_keys: Vec<PublicKey> = pool
+            .worker_id
+            .as_ref()
+            .and_then(|worker_id| self.worker_by_account_id.get(worker_id))
+            .map(|worker| worker.public_key.clone())
+            .into_iter()
+            .collect();
+        ext_intents_vault::ext(self.get_pool_account_id(pool_id))
+            .with_static_gas(GAS_MIGRATE_POOL_VAULT)
+            .with_unused_gas_weight(0)
+            .migrate(self.intents_contract_id.clone(), public_keys)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_MIGRATE_POOL_VAULT_CALLBACK)
+                    .with_unused_gas_weight(0)
+                    .on_pool_vault_migrated(pool_id, code_hash),
+            )
             .into()
     }
+
+    #[private]
+    pub fn on_pool_vault_migrated(
+        &mut self,
+        pool_id: u32,
+        code_hash: String,
+        #[callback_result] call_result: Result<(), PromiseError>,
+    ) -> bool {
+        if call_result.is_err() {
+            return false;
+        }
+
+        let pool = self.pools.get_mut(pool_id).expect("Pool not found");
+        pool.vault_code_hash = Some(code_hash);
+        self.pools.flush();
+        true
+    }
+
+    /// Set the minimum time between staging and deploying new code. Increases apply
+    /// immediately. Reductions are subject to the current delay: they can be applied with
+    /// `apply_upgrade_delay_ms` once it has passed.
+    pub fn set_upgrade_delay_ms(&mut self, upgrade_delay_ms: TimestampMs) {
+        self.assert_owner();
+
+        // Any change replaces a scheduled reduction
+        self.pending_upgrade_delay = None;
+        if upgrade_delay_ms >= self.upgrade_delay_ms {
+            self.upgrade_delay_ms = upgrade_delay_ms;
+
+            Event::UpgradeDelayUpdated {
+                upgrade_delay_ms: &upgrade_delay_ms,
+            }
+            .emit();
+            return;
+        }
+
+        let effective_at_ms = block_timestamp_ms() + self.upgrade_delay_ms;
+        self.pending_upgrade_delay = Some(PendingUpgradeDelay {
+            upgrade_delay_ms,
+            effective_at_ms,
+        });
+
+        Event::UpgradeDelayChangeScheduled {
+            upgrade_delay_ms: &upgrade_delay_ms,
+            effective_at_ms: &effective_at_ms,
+        }
+        .emit();
+    }
+
+    /// Apply the scheduled upgrade delay reduction once the current delay has passed
+    pub fn apply_upgrade_delay_ms(&mut self) {
+        let pending_delay = self
+            .pending_upgrade_delay
+            .take()
+            .expect("No pending upgrade delay change");
+        require!(
+            block_timestamp_ms() >= pending_delay.effective_at_ms,
+            "Upgrade delay change is not effective yet"
+        );
+        self.upgrade_delay_ms = pending_delay.upgrade_delay_ms;
+
+        Event::UpgradeDelayUpdated {
+            upgrade_delay_ms: &pending_delay.upgrade_delay_ms,
+        }
+        .emit();
+    }
 }
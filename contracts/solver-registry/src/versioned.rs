@@ -0,0 +1,267 @@
+//! Versioned contract state.
+//!
+//! The top-level state is migrated eagerly in `migrate`. Pool and worker entries are
+//! stored with a version tag, so `migrate` doesn't touch them: they're upgraded to the
+//! current layout when they're loaded, and rewritten in it the next time they're persisted.
+
+use std::cell::OnceCell;
+use std::io::{Read, Write};
+use std::ops::{Deref, DerefMut};
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::env::{self, block_timestamp_ms};
+use near_sdk::near;
+use near_sdk::store::{IterableMap, IterableSet, LazyOption, LookupMap, Vector};
+
+use crate::batch_deposit::DEFAULT_PENDING_DEPOSIT_TIMEOUT_MS;
+use crate::legacy::*;
+use crate::operator::DEFAULT_POOL_FEE_DELAY_MS;
+use crate::pool::PoolCreationPolicy;
+use crate::types::*;
+use crate::{Contract, Pool, Worker};
+
+/// Version of the current contract state layout
+pub const STATE_VERSION: u32 = 2;
+
+/// v0.1.0 didn't record the state version, so a missing key means `ContractV1`.
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+const STATE_KEY: &[u8] = b"STATE";
+
+pub enum VersionedContract {
+    V1(ContractV1),
+    V2(Contract),
+}
+
+impl VersionedContract {
+    /// Read the contract state stored by the previously deployed code.
+    pub fn read() -> Self {
+        let state = env::storage_read(STATE_KEY).expect("Failed to read contract state");
+        let version = env::storage_read(STATE_VERSION_KEY)
+            .map(|bytes| u32::try_from_slice(&bytes).expect("Invalid state version"))
+            .unwrap_or(1);
+
+        match version {
+            1 => Self::V1(ContractV1::try_from_slice(&state).expect("Invalid V1 state")),
+            2 => Self::V2(Contract::try_from_slice(&state).expect("Invalid V2 state")),
+            _ => env::panic_str("Unknown state version"),
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        match self {
+            Self::V1(_) => 1,
+            Self::V2(_) => 2,
+        }
+    }
+}
+
+impl From<VersionedContract> for Contract {
+    fn from(state: VersionedContract) -> Self {
+        match state {
+            VersionedContract::V1(mut state) => {
+                let mut approved_compose_hashes = IterableMap::new(Prefix::ComposeHashes);
+                for compose_hash in state.approved_compose_hashes.drain() {
+                    approved_compose_hashes.insert(
+                        compose_hash.clone(),
+                        ComposeHashInfo {
+                            compose_hash,
+                            label: None,
+                            source_repo: None,
+                            source_commit: None,
+                            approved_by: state.owner_id.clone(),
+                            approved_at_ms: block_timestamp_ms(),
+                            expires_at_ms: None,
+                        },
+                    );
+                }
+
+                Contract {
+                    owner_id: state.owner_id,
+                    intents_contract_id: state.intents_contract_id,
+                    // v0.1.0 entries stay in place and are upgraded when they're loaded
+                    pools: state.pools,
+                    approved_compose_hashes,
+                    worker_by_account_id: state.worker_by_account_id,
+                    worker_ping_timeout_ms: state.worker_ping_timeout_ms,
+                    staged_code: LazyOption::new(Prefix::StagedCode, None),
+                    staged_code_info: None,
+                    upgrade_delay_ms: 0,
+                    pending_upgrade_delay: None,
+                    staged_vault_code: LazyOption::new(Prefix::StagedVaultCode, None),
+                    vault_global_code_hash: None,
+                    pool_creation_policy: PoolCreationPolicy::Open,
+                    pool_creators: IterableSet::new(Prefix::PoolCreators),
+                    allowed_tokens: IterableMap::new(Prefix::AllowedTokens),
+                    pool_fee_delay_ms: DEFAULT_POOL_FEE_DELAY_MS,
+                    pending_deposits: LookupMap::new(Prefix::PendingDeposits),
+                    pending_deposit_timeout_ms: DEFAULT_PENDING_DEPOSIT_TIMEOUT_MS,
+                    wnear_contract_id: None,
+                    ping_events: PingEvents::All,
+                }
+            }
+            VersionedContract::V2(state) => state,
+        }
+    }
+}
+
+pub(crate) fn write_state_version() {
+    env::storage_write(
+        STATE_VERSION_KEY,
+        &near_sdk::borsh::to_vec(&STATE_VERSION).unwrap(),
+    );
+}
+
+/// Leads every pool and worker entry written since v0.2.0, followed by the layout version.
+/// v0.1.0 entries are stored untagged and can't start with it: pool entries start with
+/// their token count and worker entries with their pool ID.
+const ENTRY_VERSION_MARKER: [u8; 4] = u32::MAX.to_le_bytes();
+
+/// Layout version of the current `Pool`
+const POOL_VERSION: u8 = 2;
+
+/// Layout version of the current `Worker`
+const WORKER_VERSION: u8 = 2;
+
+/// Read the layout version of an entry. `None` means an untagged v0.1.0 entry,
+/// whose first bytes are returned so it can still be read from the start.
+fn read_entry_version<R: Read>(reader: &mut R) -> std::io::Result<(Option<u8>, [u8; 4])> {
+    let marker = <[u8; 4]>::deserialize_reader(reader)?;
+    if marker != ENTRY_VERSION_MARKER {
+        return Ok((None, marker));
+    }
+    Ok((Some(u8::deserialize_reader(reader)?), marker))
+}
+
+fn write_entry_version<W: Write>(writer: &mut W, version: u8) -> std::io::Result<()> {
+    ENTRY_VERSION_MARKER.serialize(writer)?;
+    version.serialize(writer)
+}
+
+fn unknown_entry_version() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        "Unknown entry layout version",
+    )
+}
+
+/// Pools of the contract, indexed by pool ID. Upgrades v0.1.0 entries when they're loaded,
+/// which can't be done when they're deserialized as their new collections are keyed by
+/// the pool ID.
+#[near(serializers = [borsh])]
+pub struct Pools(Vector<VersionedPool>);
+
+impl Pools {
+    pub fn new(prefix: Prefix) -> Self {
+        Self(Vector::new(prefix))
+    }
+
+    pub fn len(&self) -> u32 {
+        self.0.len()
+    }
+
+    pub fn push(&mut self, pool: Pool) {
+        self.0.push(VersionedPool::Current(pool));
+    }
+
+    pub fn get(&self, pool_id: u32) -> Option<&Pool> {
+        match self.0.get(pool_id)? {
+            VersionedPool::V1(pool, upgraded) => {
+                Some(upgraded.get_or_init(|| pool.to_pool(pool_id)))
+            }
+            VersionedPool::Current(pool) => Some(pool),
+        }
+    }
+
+    /// Get a pool to modify it. v0.1.0 entries are rewritten in the current layout.
+    pub fn get_mut(&mut self, pool_id: u32) -> Option<&mut Pool> {
+        let entry = self.0.get_mut(pool_id)?;
+        if let VersionedPool::V1(pool, upgraded) = entry {
+            let pool = upgraded.take().unwrap_or_else(|| pool.to_pool(pool_id));
+            *entry = VersionedPool::Current(pool);
+        }
+        match entry {
+            VersionedPool::V1(..) => unreachable!(),
+            VersionedPool::Current(pool) => Some(pool),
+        }
+    }
+
+    pub fn flush(&mut self) {
+        self.0.flush();
+    }
+}
+
+/// Pool entry as persisted in storage. v0.1.0 entries keep their original layout until
+/// they're modified, with the upgraded pool cached on first read.
+pub enum VersionedPool {
+    V1(PoolV1, OnceCell<Pool>),
+    Current(Pool),
+}
+
+impl BorshSerialize for VersionedPool {
+    fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        match self {
+            Self::V1(pool, _) => pool.serialize(writer),
+            Self::Current(pool) => {
+                write_entry_version(writer, POOL_VERSION)?;
+                pool.serialize(writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for VersionedPool {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        match read_entry_version(reader)? {
+            (None, head) => Ok(Self::V1(
+                PoolV1::deserialize_reader(&mut head.as_slice().chain(reader))?,
+                OnceCell::new(),
+            )),
+            (Some(POOL_VERSION), _) => Ok(Self::Current(Pool::deserialize_reader(reader)?)),
+            _ => Err(unknown_entry_version()),
+        }
+    }
+}
+
+/// Worker entry as persisted in storage. Always holds the current layout in memory,
+/// v0.1.0 entries are upgraded when they're loaded.
+pub struct VersionedWorker(Worker);
+
+impl From<Worker> for VersionedWorker {
+    fn from(worker: Worker) -> Self {
+        Self(worker)
+    }
+}
+
+impl Deref for VersionedWorker {
+    type Target = Worker;
+
+    fn deref(&self) -> &Worker {
+        &self.0
+    }
+}
+
+impl DerefMut for VersionedWorker {
+    fn deref_mut(&mut self) -> &mut Worker {
+        &mut self.0
+    }
+}
+
+impl BorshSerialize for VersionedWorker {
+    fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        write_entry_version(writer, WORKER_VERSION)?;
+        self.0.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for VersionedWorker {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        let worker = match read_entry_version(reader)? {
+            (None, head) => {
+                WorkerV1::deserialize_reader(&mut head.as_slice().chain(reader))?.into()
+            }
+            (Some(WORKER_VERSION), _) => Worker::deserialize_reader(reader)?,
+            _ => return Err(unknown_entry_version()),
+        };
+        Ok(Self(worker))
+    }
+}
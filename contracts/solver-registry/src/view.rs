@@ -1,15 +1,78 @@
 use crate::*;
+use near_sdk::json_types::U128;
 use near_sdk::AccountId;
 
 #[near]
 impl Contract {
+    /// Get the contract code version
+    pub fn get_version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    /// Get the hash of the code staged for the next upgrade, to compare with a reproducible build
+    pub fn get_staged_code(&self) -> Option<StagedCodeInfo> {
+        self.staged_code_info.clone()
+    }
+
+    /// Get the hash of the vault code deployed to new pools and pushed by `upgrade_pool_vaults`,
+    /// which is the published global contract if there's one
+    pub fn get_vault_code_hash(&self) -> String {
+        self.vault_code_hash()
+    }
+
+    /// Get the code hash of the published vault global contract
+    pub fn get_vault_global_code_hash(&self) -> Option<String> {
+        self.vault_global_code_hash.map(hex::encode)
+    }
+
+    /// Get the deposit required by `create_liquidity_pool`
+    pub fn get_create_pool_storage_deposit(&self) -> NearToken {
+        self.create_pool_storage_deposit()
+    }
+
+    pub fn get_upgrade_delay_ms(&self) -> TimestampMs {
+        self.upgrade_delay_ms
+    }
+
+    /// Get the scheduled upgrade delay reduction
+    pub fn get_pending_upgrade_delay(&self) -> Option<&PendingUpgradeDelay> {
+        self.pending_upgrade_delay.as_ref()
+    }
+
     pub fn get_owner_id(&self) -> AccountId {
         self.owner_id.clone()
     }
 
     /// Get all approved docker compose hashes
     pub fn get_approved_compose_hashes(&self) -> Vec<String> {
-        self.approved_compose_hashes.iter().cloned().collect()
+        self.approved_compose_hashes.keys().cloned().collect()
+    }
+
+    /// Get the metadata of an approved docker compose hash
+    pub fn get_compose_hash(&self, compose_hash: String) -> Option<ComposeHashInfo> {
+        self.approved_compose_hashes.get(&compose_hash).cloned()
+    }
+
+    /// Get the metadata of approved docker compose hashes with pagination
+    pub fn get_compose_hashes(&self, offset: u32, limit: u32) -> Vec<&ComposeHashInfo> {
+        self.approved_compose_hashes
+            .values()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    pub fn get_pool_creation_policy(&self) -> PoolCreationPolicy {
+        self.pool_creation_policy
+    }
+
+    pub fn get_pool_creators(&self) -> Vec<&AccountId> {
+        self.pool_creators.iter().collect()
+    }
+
+    /// Get the tokens allowed in new pools with their configuration
+    pub fn get_allowed_tokens(&self) -> Vec<(&AccountId, &TokenConfig)> {
+        self.allowed_tokens.iter().collect()
     }
 
     pub fn get_pool_len(&self) -> u32 {
@@ -24,15 +87,85 @@ impl Contract {
             shares_total_supply: p.shares_total_supply.into(),
             worker_id: p.worker_id.clone(),
             last_ping_timestamp_ms: p.last_ping_timestamp_ms,
+            allowed_compose_hashes: p.allowed_compose_hashes.clone(),
+            vault_code_hash: p.vault_code_hash.clone(),
+            status: p.status,
+            creator_id: p.creator_id.clone(),
+            operator_id: p.operator_id.clone(),
+            worker_ping_timeout_ms: p.worker_ping_timeout_ms,
+            pending_fee: p.pending_fee.clone(),
+            token_limits: p.token_limits.clone(),
+            deposit_mode: p.deposit_mode,
+            pending_worker_key: p.pending_worker_key.clone(),
         })
     }
 
+    /// Get how much of a token the pool can still accept, from the account if given.
+    /// `None` if there's no limit.
+    pub fn get_pool_remaining_capacity(
+        &self,
+        pool_id: u32,
+        token_id: AccountId,
+        account_id: Option<AccountId>,
+    ) -> Option<U128> {
+        let pool = self.pools.get(pool_id).expect("Pool not found");
+        pool.remaining_capacity(pool.token_index(&token_id), account_id.as_ref())
+            .map(U128)
+    }
+
+    /// Get the pool shares of a liquidity provider
+    pub fn get_pool_shares(&self, pool_id: u32, account_id: AccountId) -> U128 {
+        let pool = self.pools.get(pool_id).expect("Pool not found");
+        U128(pool.shares.get(&account_id).copied().unwrap_or_default())
+    }
+
+    /// Get the tokens held for a batch deposit of an account into a pool
+    pub fn get_pending_deposit(
+        &self,
+        pool_id: u32,
+        account_id: AccountId,
+    ) -> Option<&PendingDeposit> {
+        self.pending_deposits.get(&(pool_id, account_id))
+    }
+
+    pub fn get_pending_deposit_timeout_ms(&self) -> TimestampMs {
+        self.pending_deposit_timeout_ms
+    }
+
+    /// Get the deposits of an account into a pool
+    pub fn get_pool_deposit(&self, pool_id: u32, account_id: AccountId) -> Option<&PoolDeposit> {
+        self.pools
+            .get(pool_id)
+            .and_then(|pool| pool.depositors.get(&account_id))
+    }
+
+    /// Get the liquidity providers of a pool with their deposits
+    pub fn get_pool_depositors(
+        &self,
+        pool_id: u32,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<(&AccountId, &PoolDeposit)> {
+        self.pools
+            .get(pool_id)
+            .map(|pool| {
+                pool.depositors
+                    .iter()
+                    .skip(offset as usize)
+                    .take(limit as usize)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     pub fn get_worker_len(&self) -> u32 {
         self.worker_by_account_id.len()
     }
 
     pub fn get_worker(&self, account_id: AccountId) -> Option<Worker> {
-        self.worker_by_account_id.get(&account_id).cloned()
+        self.worker_by_account_id
+            .get(&account_id)
+            .map(|worker| Worker::clone(worker))
     }
 
     pub fn get_workers(&self, offset: u32, limit: u32) -> Vec<&Worker> {
@@ -40,10 +173,23 @@ impl Contract {
             .values()
             .skip(offset as usize)
             .take(limit as usize)
+            .map(|worker| &**worker)
             .collect()
     }
 
     pub fn get_worker_ping_timeout_ms(&self) -> TimestampMs {
         self.worker_ping_timeout_ms
     }
+
+    pub fn get_pool_fee_delay_ms(&self) -> TimestampMs {
+        self.pool_fee_delay_ms
+    }
+
+    pub fn get_wnear_contract_id(&self) -> Option<AccountId> {
+        self.wnear_contract_id.clone()
+    }
+
+    pub fn get_ping_events(&self) -> PingEvents {
+        self.ping_events
+    }
 }
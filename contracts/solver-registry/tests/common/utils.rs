@@ -1,11 +1,15 @@
 use std::collections::HashSet;
 use std::str::FromStr;
 
+use ed25519_dalek::{Signer, SigningKey};
 use near_contract_standards::fungible_token::{metadata::FungibleTokenMetadata, Balance};
 use near_gas::NearGas;
 use near_sdk::{AccountId, NearToken, PublicKey};
 use near_workspaces::{
-    network::Sandbox, result::ExecutionFinalResult, types::SecretKey, Account, Contract, Worker,
+    network::Sandbox,
+    result::ExecutionFinalResult,
+    types::{AccessKey, KeyType, SecretKey},
+    Account, Contract, Worker,
 };
 use serde_json::json;
 use solver_registry::{pool::PoolInfo, types::TimestampMs};
@@ -18,6 +22,9 @@ pub const SOLVER_REGISTRY_CONTRACT_WASM: &str =
     "../../target/near/solver_registry/solver_registry.wasm";
 pub const MOCK_INTENTS_CONTRACT_WASM: &str = "../../target/near/mock_intents/mock_intents.wasm";
 pub const MOCK_FT_CONTRACT_WASM: &str = "../../target/near/mock_ft/mock_ft.wasm";
+pub const INTENTS_VAULT_CONTRACT_WASM: &str = "../intents-vault/res/intents_vault.wasm";
+/// Solver registry v0.1.0, used to test state migrations
+pub const SOLVER_REGISTRY_V1_CONTRACT_WASM: &str = "tests/res/solver_registry_v0.1.0.wasm";
 
 pub async fn create_account(
     sandbox: &Worker<Sandbox>,
@@ -131,6 +138,59 @@ pub async fn ft_transfer(
     Ok(result)
 }
 
+pub async fn ft_balance_of(
+    ft: &Contract,
+    account_id: &near_workspaces::AccountId,
+) -> Result<Balance, Box<dyn std::error::Error>> {
+    let result = ft
+        .view("ft_balance_of")
+        .args_json(json!({"account_id": account_id}))
+        .await?;
+    let balance: near_sdk::json_types::U128 = serde_json::from_slice(&result.result).unwrap();
+
+    Ok(balance.0)
+}
+
+// Helper function to deposit a token into the mock intents contract for the sender
+pub async fn deposit_into_intents(
+    mock_intents: &Contract,
+    user: &Account,
+    ft: &Contract,
+    amount: Balance,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let result = user
+        .call(ft.id(), "ft_transfer_call")
+        .args_json(json!({
+            "receiver_id": mock_intents.id(),
+            "amount": amount.to_string(),
+            "msg": "",
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    Ok(())
+}
+
+// Helper function to get the balance of a NEP-141 token held in the mock intents contract
+pub async fn mt_balance_of(
+    mock_intents: &Contract,
+    account_id: &AccountId,
+    ft: &Contract,
+) -> Result<Balance, Box<dyn std::error::Error>> {
+    let balance: near_sdk::json_types::U128 = mock_intents
+        .view("mt_balance_of")
+        .args_json(json!({"account_id": account_id, "token_id": format!("nep141:{}", ft.id())}))
+        .await?
+        .json()?;
+    Ok(balance.0)
+}
+
 pub async fn deploy_mock_intents(
     sandbox: &Worker<Sandbox>,
 ) -> Result<Contract, Box<dyn std::error::Error>> {
@@ -149,14 +209,84 @@ pub async fn deploy_mock_intents(
     Ok(mock_intents_contract)
 }
 
+// Helper function to make calls to the mock intents contract fail on demand
+pub async fn set_mock_intents_failures(
+    mock_intents: &Contract,
+    failures: serde_json::Value,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let result = mock_intents
+        .call("set_failures")
+        .args_json(json!({"failures": failures}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    Ok(())
+}
+
+// Helper function to sign an intent payload with an ed25519 secret key
+pub fn sign_intent_payload(secret_key: &str, payload: serde_json::Value) -> serde_json::Value {
+    let keypair: [u8; 64] = bs58::decode(secret_key.strip_prefix("ed25519:").unwrap())
+        .into_vec()
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let signing_key = SigningKey::from_keypair_bytes(&keypair).unwrap();
+    let payload = payload.to_string();
+    let signature = signing_key.sign(payload.as_bytes());
+    json!({
+        "payload": payload,
+        "public_key": format!(
+            "ed25519:{}",
+            bs58::encode(signing_key.verifying_key().as_bytes()).into_string()
+        ),
+        "signature": format!("ed25519:{}", bs58::encode(signature.to_bytes()).into_string()),
+    })
+}
+
+// Helper function to execute signed intents in the mock intents contract
+pub async fn execute_intents(
+    mock_intents: &Contract,
+    relayer: &Account,
+    signed: Vec<serde_json::Value>,
+) -> Result<ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = relayer
+        .call(mock_intents.id(), "execute_intents")
+        .args_json(json!({"signed": signed}))
+        .gas(NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    print_logs(&result);
+    Ok(result)
+}
+
 pub async fn deploy_solver_registry(
     sandbox: &Worker<Sandbox>,
     intents_contract: &Contract,
     owner: &Account,
     worker_ping_timeout_ms: TimestampMs,
 ) -> Result<Contract, Box<dyn std::error::Error>> {
-    let solver_registry_contract_wasm =
-        std::fs::read(SOLVER_REGISTRY_CONTRACT_WASM).expect("Contract wasm not found");
+    deploy_solver_registry_from_wasm(
+        sandbox,
+        SOLVER_REGISTRY_CONTRACT_WASM,
+        intents_contract,
+        owner,
+        worker_ping_timeout_ms,
+    )
+    .await
+}
+
+pub async fn deploy_solver_registry_from_wasm(
+    sandbox: &Worker<Sandbox>,
+    wasm_path: &str,
+    intents_contract: &Contract,
+    owner: &Account,
+    worker_ping_timeout_ms: TimestampMs,
+) -> Result<Contract, Box<dyn std::error::Error>> {
+    let solver_registry_contract_wasm = std::fs::read(wasm_path).expect("Contract wasm not found");
     let solver_registry_account = create_account(sandbox, "solver-registry", 100).await?;
     let solver_registry_contract = solver_registry_account
         .deploy(&solver_registry_contract_wasm)
@@ -205,6 +335,29 @@ pub async fn deposit_into_pool(
     Ok(result)
 }
 
+// Helper function for the pool operator to make the first deposits of both tokens
+pub async fn seed_pool(
+    solver_registry: &Contract,
+    operator: &Account,
+    pool_id: u32,
+    wnear: &Contract,
+    usdc: &Contract,
+    wnear_amount: Balance,
+    usdc_amount: Balance,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for (ft, amount) in [(wnear, wnear_amount), (usdc, usdc_amount)] {
+        let _ = storage_deposit(ft, operator).await?;
+        let _ = ft_transfer(ft, ft.as_account(), operator, amount).await?;
+        let result = deposit_into_pool(solver_registry, operator, pool_id, ft, amount).await?;
+        assert!(
+            result.is_success(),
+            "{:#?}",
+            result.into_result().unwrap_err()
+        );
+    }
+    Ok(())
+}
+
 // Helper function to print execution logs
 pub fn print_logs(result: &near_workspaces::result::ExecutionFinalResult) {
     for (i, log) in result.logs().iter().enumerate() {
@@ -316,6 +469,10 @@ pub async fn setup_test_environment(
     )
     .await?;
 
+    // Allow the test tokens in pools
+    allow_token(&owner, &solver_registry, &wnear, 24, 0).await?;
+    allow_token(&owner, &solver_registry, &usdc, 6, 0).await?;
+
     Ok((
         wnear,
         usdc,
@@ -352,6 +509,31 @@ pub async fn create_liquidity_pool(
     Ok(())
 }
 
+// Helper function to allow a token in pools
+pub async fn allow_token(
+    owner: &Account,
+    solver_registry: &Contract,
+    token: &Contract,
+    decimals: u8,
+    min_deposit: u128,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let result = owner
+        .call(solver_registry.id(), "allow_token")
+        .args_json(json!({
+            "token_id": token.id(),
+            "decimals": decimals,
+            "min_deposit": min_deposit.to_string(),
+        }))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    Ok(())
+}
+
 // Helper function to approve compose hash
 pub async fn approve_compose_hash(
     owner: &Account,
@@ -446,6 +628,17 @@ pub async fn wait_for_worker_timeout(timeout_seconds: u64) {
     // Add 1 second buffer
 }
 
+// Helper function to check for the refund of the yoctoNEAR attached to a failed worker registration
+pub fn is_refund_event(log: &str, worker: &Account) -> bool {
+    log.strip_prefix("EVENT_JSON:")
+        .and_then(|log| serde_json::from_str::<serde_json::Value>(log).ok())
+        .is_some_and(|event| {
+            event["event"] == "refunded"
+                && event["data"][0]["receiver_id"] == worker.id().as_str()
+                && event["data"][0]["amount"] == "1"
+        })
+}
+
 // Helper function to get worker info
 pub async fn get_worker_info(
     solver_registry: &Contract,
@@ -532,3 +725,23 @@ pub fn get_pool_account_id(solver_registry: &Contract, pool_id: u32) -> AccountI
         .parse()
         .unwrap()
 }
+
+// Helper function to sign as a pool account, to change its keys without the registry
+pub async fn get_pool_account(
+    sandbox: &Worker<Sandbox>,
+    solver_registry: &Contract,
+    pool_id: u32,
+) -> Result<Account, Box<dyn std::error::Error>> {
+    let pool_account_id = get_pool_account_id(solver_registry, pool_id);
+    let secret_key = SecretKey::from_random(KeyType::ED25519);
+    sandbox
+        .patch(&pool_account_id)
+        .access_key(secret_key.public_key(), AccessKey::full_access())
+        .transact()
+        .await?;
+    Ok(Account::from_secret_key(
+        pool_account_id,
+        secret_key,
+        sandbox,
+    ))
+}
@@ -0,0 +1,226 @@
+use near_gas::NearGas;
+use near_sdk::json_types::U128;
+use near_sdk::NearToken;
+use serde_json::json;
+use solver_registry::pool::{PoolDeposit, PoolStatus};
+
+mod common;
+
+use common::utils::*;
+
+#[tokio::test]
+async fn test_inactive_worker_key_removal_failure() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for failed removal of an inactive worker key...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 5 * 1000).await?;
+
+    // Create a liquidity pool and register Alice as its worker
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    approve_compose_hash(&owner, &solver_registry).await?;
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let alice_worker = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should be registered as a worker");
+
+    // Alice times out, but NEAR Intents fails to remove her key
+    wait_for_worker_timeout(5).await;
+    set_mock_intents_failures(&mock_intents, json!({"remove_public_key": true})).await?;
+
+    let result = register_worker_bob(&bob, &solver_registry, 0).await?;
+    assert!(result.is_success(), "Registration should not fail");
+    assert!(
+        result
+            .logs()
+            .iter()
+            .any(|log| log.contains("worker_registration_failed")),
+        "Failed registration should emit an event"
+    );
+    assert!(
+        result.logs().iter().any(|log| is_refund_event(log, &bob)),
+        "The attached deposit should be refunded"
+    );
+    let registered: bool = result.json()?;
+    assert!(
+        !registered,
+        "Bob's registration should fail when the inactive worker key can't be removed"
+    );
+
+    // Alice remains the pool worker with her key, and Bob's key is no longer pending
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.worker_id.as_ref(), Some(alice.id()));
+    assert!(pool.pending_worker_key.is_none());
+    assert!(get_worker_info(&solver_registry, &bob).await?.is_none());
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert_eq!(public_keys.len(), 1);
+    assert!(public_keys.contains(&alice_worker.public_key));
+
+    // Bob replaces Alice once the key can be removed
+    set_mock_intents_failures(&mock_intents, json!({})).await?;
+    let result = register_worker_bob(&bob, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.worker_id.as_ref(), Some(bob.id()));
+    assert!(get_worker_info(&solver_registry, &alice).await?.is_none());
+
+    println!("Test passed: Failed removal of an inactive worker key keeps the pool unchanged");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_worker_key_revocation_failure() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for failed revocation of a worker key...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool and register Alice as its worker
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    approve_compose_hash(&owner, &solver_registry).await?;
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Winding down fails if the worker key can't be revoked
+    set_mock_intents_failures(&mock_intents, json!({"remove_public_key": true})).await?;
+    let result = owner
+        .call(solver_registry.id(), "wind_down_pool")
+        .args_json(json!({"pool_id": 0}))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Winding down should fail when the worker key can't be revoked"
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.status, PoolStatus::Active);
+    assert_eq!(pool.worker_id.as_ref(), Some(alice.id()));
+    assert!(get_worker_info(&solver_registry, &alice).await?.is_some());
+
+    // The pool winds down once the key can be revoked
+    set_mock_intents_failures(&mock_intents, json!({})).await?;
+    let result = owner
+        .call(solver_registry.id(), "wind_down_pool")
+        .args_json(json!({"pool_id": 0}))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.status, PoolStatus::WindingDown);
+    assert!(pool.worker_id.is_none());
+
+    println!("Test passed: Failed revocation of a worker key keeps the pool unchanged");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deposit_refunds() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for refunds of pool deposits...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create and seed a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    seed_pool(
+        &solver_registry,
+        &owner,
+        0,
+        &wnear,
+        &usdc,
+        1_000_000,
+        1_000_000,
+    )
+    .await?;
+    let _ = ft_transfer(&usdc, usdc.as_account(), &alice, 10_000_000).await?;
+
+    // NEAR Intents refunds the whole deposit
+    set_mock_intents_failures(&mock_intents, json!({"ft_refund_bps": 10_000})).await?;
+    let _ = deposit_into_pool(&solver_registry, &alice, 0, &usdc, 1_000_000).await?;
+    assert_eq!(ft_balance_of(&usdc, alice.id()).await?, 10_000_000);
+    let deposit: Option<PoolDeposit> = solver_registry
+        .view("get_pool_deposit")
+        .args_json(json!({"pool_id": 0, "account_id": alice.id()}))
+        .await?
+        .json()?;
+    assert!(deposit.is_none(), "Refunded deposit should not be recorded");
+    let shares: U128 = solver_registry
+        .view("get_pool_shares")
+        .args_json(json!({"pool_id": 0, "account_id": alice.id()}))
+        .await?
+        .json()?;
+    assert_eq!(shares, U128(0));
+
+    // NEAR Intents refunds half of the deposit
+    set_mock_intents_failures(&mock_intents, json!({"ft_refund_bps": 5_000})).await?;
+    let _ = deposit_into_pool(&solver_registry, &alice, 0, &usdc, 1_000_000).await?;
+    assert_eq!(ft_balance_of(&usdc, alice.id()).await?, 9_500_000);
+    let deposit: Option<PoolDeposit> = solver_registry
+        .view("get_pool_deposit")
+        .args_json(json!({"pool_id": 0, "account_id": alice.id()}))
+        .await?
+        .json()?;
+    let deposit = deposit.expect("Used part of the deposit should be recorded");
+    assert_eq!(deposit.amounts, vec![U128(0), U128(500_000)]);
+    let shares: U128 = solver_registry
+        .view("get_pool_shares")
+        .args_json(json!({"pool_id": 0, "account_id": alice.id()}))
+        .await?
+        .json()?;
+    assert!(shares.0 > 0, "Shares should be minted for the used part");
+
+    // NEAR Intents runs out of gas, so the deposit is refunded
+    set_mock_intents_failures(&mock_intents, json!({"burn_gas": true})).await?;
+    let _ = deposit_into_pool(&solver_registry, &alice, 0, &usdc, 1_000_000).await?;
+    assert_eq!(ft_balance_of(&usdc, alice.id()).await?, 9_500_000);
+    let shares_after: U128 = solver_registry
+        .view("get_pool_shares")
+        .args_json(json!({"pool_id": 0, "account_id": alice.id()}))
+        .await?
+        .json()?;
+    assert_eq!(shares_after, shares);
+
+    // The reserved capacity of the refunded amounts is released
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.amounts, vec![U128(1_000_000), U128(1_500_000)]);
+
+    // Only the used deposits are credited to the pool account in NEAR Intents
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    assert_eq!(
+        mt_balance_of(&mock_intents, &pool_account_id, &usdc).await?,
+        1_500_000
+    );
+
+    println!("Test passed: Refunded deposits are returned and not credited");
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use serde_json::json;
+use solver_registry::types::ComposeHashInfo;
+
+mod common;
+
+use common::constants::*;
+use common::utils::*;
+
+const OTHER_COMPOSE_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+
+#[tokio::test]
+async fn test_approve_compose_hash_with_metadata() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for compose hash metadata...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (_wnear, _usdc, owner, _alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Approve compose hash with metadata
+    let result = owner
+        .call(solver_registry.id(), "approve_compose_hash")
+        .args_json(json!({
+            "compose_hash": COMPOSE_HASH,
+            "label": "amm-solver",
+            "source_repo": "https://github.com/think-in-universe/near-intents-tee-amm-solver",
+            "source_commit": "3f1c2a7",
+        }))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Verify the metadata is recorded
+    let result = solver_registry
+        .view("get_compose_hash")
+        .args_json(json!({"compose_hash": COMPOSE_HASH}))
+        .await?;
+    let info: Option<ComposeHashInfo> = serde_json::from_slice(&result.result).unwrap();
+    let info = info.expect("Compose hash should be approved");
+    assert_eq!(info.label.as_deref(), Some("amm-solver"));
+    assert_eq!(info.source_commit.as_deref(), Some("3f1c2a7"));
+    assert_eq!(&info.approved_by, owner.id());
+    assert!(info.approved_at_ms > 0, "Approval time should be recorded");
+    assert!(info.expires_at_ms.is_none(), "Expiry should not be set");
+
+    // Expiry in the past is rejected
+    let result = owner
+        .call(solver_registry.id(), "approve_compose_hash")
+        .args_json(json!({
+            "compose_hash": OTHER_COMPOSE_HASH,
+            "expires_at_ms": 1,
+        }))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Approving a compose hash with an expiry in the past should fail"
+    );
+
+    println!("Test passed: Compose hash metadata is recorded");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pool_compose_hash_allowlist() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for pool compose hash allowlist...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Approve both compose hashes
+    approve_compose_hash(&owner, &solver_registry).await?;
+    let result = owner
+        .call(solver_registry.id(), "approve_compose_hash")
+        .args_json(json!({"compose_hash": OTHER_COMPOSE_HASH}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // A non-owner cannot restrict the pool
+    let result = alice
+        .call(solver_registry.id(), "set_pool_compose_hashes")
+        .args_json(json!({"pool_id": 0, "compose_hashes": [OTHER_COMPOSE_HASH]}))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Non-owner should not be able to set the pool compose hashes"
+    );
+
+    // Restrict pool 0 to the other compose hash
+    let result = owner
+        .call(solver_registry.id(), "set_pool_compose_hashes")
+        .args_json(json!({"pool_id": 0, "compose_hashes": [OTHER_COMPOSE_HASH]}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(
+        pool.allowed_compose_hashes,
+        Some(vec![OTHER_COMPOSE_HASH.to_string()])
+    );
+
+    // Alice runs a compose hash that is not allowed for pool 0
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        !result.is_success(),
+        "Worker registration should fail with a compose hash not allowed for the pool"
+    );
+
+    // Lift the restriction
+    let result = owner
+        .call(solver_registry.id(), "set_pool_compose_hashes")
+        .args_json(json!({"pool_id": 0, "compose_hashes": null}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed once the pool allows all approved hashes: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    println!("Test passed: Pool compose hash allowlist is enforced");
+
+    Ok(())
+}
@@ -0,0 +1,412 @@
+use near_gas::NearGas;
+use near_sdk::{AccountId, NearToken};
+use serde_json::json;
+use solver_registry::pool::PoolStatus;
+
+mod common;
+
+use common::utils::*;
+
+#[tokio::test]
+async fn test_create_pool_with_vault_global_contract() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for pool creation with the vault global contract...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, _alice, _bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // A full copy of the vault code needs 1.5 NEAR
+    let result = solver_registry
+        .view("get_create_pool_storage_deposit")
+        .await?;
+    let storage_deposit: NearToken = serde_json::from_slice(&result.result).unwrap();
+    assert_eq!(storage_deposit, NearToken::from_millinear(1500));
+
+    // Publish the vault as a global contract
+    let result = owner
+        .call(solver_registry.id(), "publish_vault_global_contract")
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "Publishing the vault global contract should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = solver_registry.view("get_vault_global_code_hash").await?;
+    let global_code_hash: Option<String> = serde_json::from_slice(&result.result).unwrap();
+    let global_code_hash = global_code_hash.expect("Vault global contract should be published");
+
+    // Referencing the global contract is much cheaper
+    let result = solver_registry
+        .view("get_create_pool_storage_deposit")
+        .await?;
+    let storage_deposit: NearToken = serde_json::from_slice(&result.result).unwrap();
+    assert_eq!(storage_deposit, NearToken::from_millinear(100));
+
+    let result = solver_registry
+        .call("create_liquidity_pool")
+        .args_json(json!({
+            "token_ids": [wnear.id(), usdc.id()],
+            "fee": 300
+        }))
+        .deposit(storage_deposit)
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "Pool creation should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // The pool vault runs the global contract
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.vault_code_hash, Some(global_code_hash));
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    let result = sandbox.view(&pool_account_id, "get_version").await?;
+    let vault_version: String = serde_json::from_slice(&result.result).unwrap();
+    println!("\n [LOG] Vault version: {}", vault_version);
+
+    // The vault is initialized with the registry and NEAR Intents account IDs
+    let registry_id: AccountId = sandbox
+        .view(&pool_account_id, "get_registry_id")
+        .await?
+        .json()?;
+    assert_eq!(&registry_id, solver_registry.id());
+    let intents_contract_id: AccountId = sandbox
+        .view(&pool_account_id, "get_intents_contract_id")
+        .await?
+        .json()?;
+    assert_eq!(&intents_contract_id, mock_intents.id());
+
+    println!("Test passed: Pools reference the vault global contract");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_pool_refunds_excess_deposit() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for pool creation refunds...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, _owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Invalid parameters are rejected before the deposit is used
+    let result = alice
+        .call(solver_registry.id(), "create_liquidity_pool")
+        .args_json(json!({
+            "token_ids": [wnear.id(), wnear.id()],
+            "fee": 300
+        }))
+        .deposit(NearToken::from_millinear(1500))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Pool creation with identical tokens should fail"
+    );
+    let result = alice
+        .call(solver_registry.id(), "create_liquidity_pool")
+        .args_json(json!({
+            "token_ids": [wnear.id(), usdc.id()],
+            "fee": 10_000
+        }))
+        .deposit(NearToken::from_millinear(1500))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Pool creation with a fee of 100% should fail"
+    );
+
+    // Attach more than needed
+    let balance_before = alice.view_account().await?.balance;
+    let result = alice
+        .call(solver_registry.id(), "create_liquidity_pool")
+        .args_json(json!({
+            "token_ids": [wnear.id(), usdc.id()],
+            "fee": 300
+        }))
+        .deposit(NearToken::from_near(3))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "Pool creation should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let balance_after = alice.view_account().await?.balance;
+
+    // Only the 1.5 NEAR storage deposit and gas are spent
+    let spent = balance_before.saturating_sub(balance_after);
+    assert!(
+        spent < NearToken::from_millinear(1600),
+        "Excess deposit should be refunded, spent {}",
+        spent
+    );
+
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.status, PoolStatus::Active);
+
+    println!("Test passed: Excess pool creation deposit is refunded");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_pool_failure_refunds_deposit() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for failed pool creation...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, _owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Take the account of the first pool, so that its creation fails
+    let _ = solver_registry
+        .as_account()
+        .create_subaccount("pool-0")
+        .initial_balance(NearToken::from_near(1))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let balance_before = alice.view_account().await?.balance;
+    let result = alice
+        .call(solver_registry.id(), "create_liquidity_pool")
+        .args_json(json!({
+            "token_ids": [wnear.id(), usdc.id()],
+            "fee": 300
+        }))
+        .deposit(NearToken::from_near(2))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    let pool_id: Option<u32> = result.json()?;
+    assert_eq!(pool_id, None, "Pool creation should fail");
+    let balance_after = alice.view_account().await?.balance;
+
+    // The whole deposit is refunded
+    let spent = balance_before.saturating_sub(balance_after);
+    assert!(
+        spent < NearToken::from_millinear(100),
+        "Deposit should be refunded, spent {}",
+        spent
+    );
+
+    // The pool ID stays reserved and the next pool gets a new ID
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.status, PoolStatus::Failed);
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    let pool = get_pool_info(&solver_registry, 1).await?;
+    assert_eq!(pool.status, PoolStatus::Active);
+
+    println!("Test passed: Failed pool creation refunds the deposit");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pool_creation_policy() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for pool creation policy...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    let create_pool = |account: near_workspaces::Account| {
+        let registry_id = solver_registry.id().clone();
+        let token_ids = json!([wnear.id(), usdc.id()]);
+        async move {
+            account
+                .call(&registry_id, "create_liquidity_pool")
+                .args_json(json!({"token_ids": token_ids, "fee": 300}))
+                .deposit(NearToken::from_millinear(1500))
+                .gas(NearGas::from_tgas(300))
+                .transact()
+                .await
+        }
+    };
+
+    // A non-owner cannot change the policy
+    let result = alice
+        .call(solver_registry.id(), "set_pool_creation_policy")
+        .args_json(json!({"policy": "OwnerOnly"}))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Non-owner should not be able to set the pool creation policy"
+    );
+
+    // Only the owner can create pools
+    let result = owner
+        .call(solver_registry.id(), "set_pool_creation_policy")
+        .args_json(json!({"policy": "OwnerOnly"}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = create_pool(alice.clone()).await?;
+    assert!(
+        !result.is_success(),
+        "Pool creation by a non-owner should fail under the OwnerOnly policy"
+    );
+    let result = create_pool(owner.clone()).await?;
+    assert!(
+        result.is_success(),
+        "Pool creation by the owner should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Only whitelisted creators can create pools
+    let result = owner
+        .call(solver_registry.id(), "set_pool_creation_policy")
+        .args_json(json!({"policy": "Whitelisted"}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = owner
+        .call(solver_registry.id(), "add_pool_creator")
+        .args_json(json!({"account_id": alice.id()}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = create_pool(bob.clone()).await?;
+    assert!(
+        !result.is_success(),
+        "Pool creation by a non-whitelisted account should fail"
+    );
+    let result = create_pool(alice.clone()).await?;
+    assert!(
+        result.is_success(),
+        "Pool creation by a whitelisted account should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Removed creators can no longer create pools
+    let result = owner
+        .call(solver_registry.id(), "remove_pool_creator")
+        .args_json(json!({"account_id": alice.id()}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = create_pool(alice.clone()).await?;
+    assert!(
+        !result.is_success(),
+        "Pool creation by a removed creator should fail"
+    );
+
+    let pool_len: u32 = solver_registry.view("get_pool_len").await?.json()?;
+    assert_eq!(pool_len, 2);
+
+    println!("Test passed: Pool creation policy is enforced");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pool_token_allowlist() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for pool token allowlist...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Tokens that are not allowed are rejected
+    let other_token = create_ft(&sandbox, "Other Token", "OTHER", 18, 1_000_000_000).await?;
+    let result = alice
+        .call(solver_registry.id(), "create_liquidity_pool")
+        .args_json(json!({
+            "token_ids": [wnear.id(), other_token.id()],
+            "fee": 300
+        }))
+        .deposit(NearToken::from_millinear(1500))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Pool creation with a token that is not allowed should fail"
+    );
+
+    // Require at least 1 USDC per deposit
+    allow_token(&owner, &solver_registry, &usdc, 6, 1_000_000).await?;
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    seed_pool(
+        &solver_registry,
+        &owner,
+        0,
+        &wnear,
+        &usdc,
+        1_000_000,
+        1_000_000,
+    )
+    .await?;
+
+    let _ = ft_transfer(&usdc, usdc.as_account(), &alice, 10_000_000).await?;
+    let _ = deposit_into_pool(&solver_registry, &alice, 0, &usdc, 500_000).await?;
+    assert_eq!(
+        ft_balance_of(&usdc, alice.id()).await?,
+        10_000_000,
+        "Deposit below the minimum should be refunded"
+    );
+    let _ = deposit_into_pool(&solver_registry, &alice, 0, &usdc, 1_000_000).await?;
+    assert_eq!(ft_balance_of(&usdc, alice.id()).await?, 9_000_000);
+
+    // Removing a token doesn't affect existing pools, but blocks new ones
+    let result = owner
+        .call(solver_registry.id(), "remove_token")
+        .args_json(json!({"token_id": usdc.id()}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.token_ids[1].as_str(), usdc.id().as_str());
+    let result = alice
+        .call(solver_registry.id(), "create_liquidity_pool")
+        .args_json(json!({
+            "token_ids": [wnear.id(), usdc.id()],
+            "fee": 300
+        }))
+        .deposit(NearToken::from_millinear(1500))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Pool creation with a removed token should fail"
+    );
+
+    println!("Test passed: Pool token allowlist is enforced");
+
+    Ok(())
+}
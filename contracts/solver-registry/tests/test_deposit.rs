@@ -0,0 +1,832 @@
+use near_gas::NearGas;
+use near_sdk::json_types::U128;
+use near_sdk::NearToken;
+use serde_json::json;
+use solver_registry::pool::PoolDeposit;
+use solver_registry::types::PendingDeposit;
+
+mod common;
+
+use common::utils::*;
+
+#[tokio::test]
+async fn test_deposit_ledger() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for pool deposit ledger...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create and seed a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    seed_pool(
+        &solver_registry,
+        &owner,
+        0,
+        &wnear,
+        &usdc,
+        1_000_000,
+        1_000_000,
+    )
+    .await?;
+
+    let _ = ft_transfer(&usdc, usdc.as_account(), &alice, 10_000_000).await?;
+    let _ = ft_transfer(&usdc, usdc.as_account(), &bob, 10_000_000).await?;
+    let _ = ft_transfer(&wnear, wnear.as_account(), &alice, 10_000).await?;
+
+    // Alice deposits both tokens, USDC twice
+    let result = deposit_into_pool(&solver_registry, &alice, 0, &usdc, 1_000_000).await?;
+    assert!(
+        result
+            .logs()
+            .iter()
+            .any(|log| log.contains("deposit_into_pool")),
+        "Deposit should emit an event"
+    );
+    let _ = deposit_into_pool(&solver_registry, &alice, 0, &usdc, 2_000_000).await?;
+    let _ = deposit_into_pool(&solver_registry, &alice, 0, &wnear, 10_000).await?;
+
+    // Bob deposits USDC
+    let _ = deposit_into_pool(&solver_registry, &bob, 0, &usdc, 5_000_000).await?;
+
+    let deposit: Option<PoolDeposit> = solver_registry
+        .view("get_pool_deposit")
+        .args_json(json!({"pool_id": 0, "account_id": alice.id()}))
+        .await?
+        .json()?;
+    let deposit = deposit.expect("Alice's deposits should be recorded");
+    assert_eq!(deposit.amounts, vec![U128(10_000), U128(3_000_000)]);
+    assert!(deposit.last_deposit_at_ms > 0);
+
+    // Page through the depositors
+    let depositors: Vec<(String, PoolDeposit)> = solver_registry
+        .view("get_pool_depositors")
+        .args_json(json!({"pool_id": 0, "offset": 0, "limit": 10}))
+        .await?
+        .json()?;
+    assert_eq!(depositors.len(), 3);
+    assert_eq!(depositors[2].0, bob.id().to_string());
+    assert_eq!(depositors[2].1.amounts, vec![U128(0), U128(5_000_000)]);
+
+    let depositors: Vec<(String, PoolDeposit)> = solver_registry
+        .view("get_pool_depositors")
+        .args_json(json!({"pool_id": 0, "offset": 1, "limit": 10}))
+        .await?
+        .json()?;
+    assert_eq!(depositors.len(), 2);
+
+    println!("Test passed: Deposits are recorded per account");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deposit_limits() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for pool deposit limits...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create and seed a liquidity pool with 1 USDC
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    seed_pool(
+        &solver_registry,
+        &owner,
+        0,
+        &wnear,
+        &usdc,
+        1_000_000,
+        1_000_000,
+    )
+    .await?;
+
+    // Inconsistent limits are rejected
+    for limits in [
+        json!({"max_pool_amount": null, "max_account_amount": "400000", "min_deposit": "500000"}),
+        json!({"max_pool_amount": "2000000", "max_account_amount": "3000000", "min_deposit": null}),
+    ] {
+        let result = owner
+            .call(solver_registry.id(), "set_pool_token_limits")
+            .args_json(json!({"pool_id": 0, "token_id": usdc.id(), "limits": limits}))
+            .transact()
+            .await?;
+        assert!(
+            !result.is_success(),
+            "Inconsistent limits should be rejected: {limits}"
+        );
+    }
+
+    // Cap the pool at 6 USDC, 3 USDC per account, with deposits of at least 0.5 USDC
+    let result = owner
+        .call(solver_registry.id(), "set_pool_token_limits")
+        .args_json(json!({
+            "pool_id": 0,
+            "token_id": usdc.id(),
+            "limits": {
+                "max_pool_amount": "6000000",
+                "max_account_amount": "3000000",
+                "min_deposit": "500000",
+            }
+        }))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    let _ = ft_transfer(&usdc, usdc.as_account(), &alice, 10_000_000).await?;
+    let _ = ft_transfer(&usdc, usdc.as_account(), &bob, 10_000_000).await?;
+
+    // Alice's deposit is capped by the account limit
+    let _ = deposit_into_pool(&solver_registry, &alice, 0, &usdc, 4_000_000).await?;
+    assert_eq!(ft_balance_of(&usdc, alice.id()).await?, 7_000_000);
+
+    // Bob's deposit below the minimum is refunded
+    let _ = deposit_into_pool(&solver_registry, &bob, 0, &usdc, 400_000).await?;
+    assert_eq!(ft_balance_of(&usdc, bob.id()).await?, 10_000_000);
+
+    let capacity: Option<U128> = solver_registry
+        .view("get_pool_remaining_capacity")
+        .args_json(json!({"pool_id": 0, "token_id": usdc.id(), "account_id": bob.id()}))
+        .await?
+        .json()?;
+    assert_eq!(capacity, Some(U128(2_000_000)));
+
+    // Bob's deposit is capped by the pool limit
+    let _ = deposit_into_pool(&solver_registry, &bob, 0, &usdc, 3_000_000).await?;
+    assert_eq!(ft_balance_of(&usdc, bob.id()).await?, 8_000_000);
+
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.amounts, vec![U128(1_000_000), U128(6_000_000)]);
+
+    let capacity: Option<U128> = solver_registry
+        .view("get_pool_remaining_capacity")
+        .args_json(json!({"pool_id": 0, "token_id": usdc.id()}))
+        .await?
+        .json()?;
+    assert_eq!(capacity, Some(U128(0)));
+
+    // wNEAR has no limits
+    let capacity: Option<U128> = solver_registry
+        .view("get_pool_remaining_capacity")
+        .args_json(json!({"pool_id": 0, "token_id": wnear.id(), "account_id": bob.id()}))
+        .await?
+        .json()?;
+    assert_eq!(capacity, None);
+
+    println!("Test passed: Pool deposit limits are enforced");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deposit_modes() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for pool deposit modes...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Only the operator can deposit until the pool holds both tokens
+    let _ = ft_transfer(&usdc, usdc.as_account(), &alice, 10_000_000).await?;
+    let _ = ft_transfer(&usdc, usdc.as_account(), &bob, 10_000_000).await?;
+    let _ = deposit_into_pool(&solver_registry, &alice, 0, &usdc, 1_000_000).await?;
+    assert_eq!(
+        ft_balance_of(&usdc, alice.id()).await?,
+        10_000_000,
+        "Deposit into a pool that is not seeded should be refunded"
+    );
+
+    // The owner seeds the pool with 1 NEAR and 2 USDC
+    seed_pool(
+        &solver_registry,
+        &owner,
+        0,
+        &wnear,
+        &usdc,
+        1_000_000_000_000_000_000_000_000,
+        2_000_000,
+    )
+    .await?;
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(
+        pool.shares_total_supply,
+        U128(2_000_000_000_000_000_000_000_000)
+    );
+
+    // Single-sided: 1 USDC is worth a quarter of the pool
+    let _ = deposit_into_pool(&solver_registry, &alice, 0, &usdc, 1_000_000).await?;
+    assert_eq!(ft_balance_of(&usdc, alice.id()).await?, 9_000_000);
+    let shares: U128 = solver_registry
+        .view("get_pool_shares")
+        .args_json(json!({"pool_id": 0, "account_id": alice.id()}))
+        .await?
+        .json()?;
+    assert_eq!(shares, U128(500_000_000_000_000_000_000_000));
+
+    // Balanced with 1% tolerance: a single token isn't accepted on its own
+    let result = owner
+        .call(solver_registry.id(), "set_pool_deposit_mode")
+        .args_json(json!({"pool_id": 0, "deposit_mode": {"Balanced": {"tolerance_bps": 100}}}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let _ = deposit_into_pool(&solver_registry, &bob, 0, &usdc, 1_000_000).await?;
+    assert_eq!(
+        ft_balance_of(&usdc, bob.id()).await?,
+        10_000_000,
+        "A single-sided deposit should be refunded"
+    );
+
+    // The pool holds 1 NEAR and 3 USDC, so 0.1 NEAR matches 0.3 USDC, plus 1% of it
+    const ONE_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+    let _ = ft_transfer(&wnear, wnear.as_account(), &bob, ONE_NEAR).await?;
+    let _ = batch_deposit_into_pool(&solver_registry, &bob, 0, &wnear, ONE_NEAR / 10).await?;
+    let _ = batch_deposit_into_pool(&solver_registry, &bob, 0, &usdc, 320_000).await?;
+    assert_eq!(ft_balance_of(&wnear, bob.id()).await?, ONE_NEAR * 9 / 10);
+    assert_eq!(
+        ft_balance_of(&usdc, bob.id()).await?,
+        9_697_000,
+        "The USDC beyond the tolerance should be refunded"
+    );
+    let shares: U128 = solver_registry
+        .view("get_pool_shares")
+        .args_json(json!({"pool_id": 0, "account_id": bob.id()}))
+        .await?
+        .json()?;
+    assert_eq!(shares, U128(251_250_000_000_000_000_000_000));
+
+    println!("Test passed: Deposits are accepted and valued by the pool deposit mode");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deposit_of_unheld_token() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for deposits of a token the pool doesn't hold...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, _alice, bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Bob creates a liquidity pool and deposits wNEAR only
+    let result = bob
+        .call(solver_registry.id(), "create_liquidity_pool")
+        .args_json(json!({
+            "token_ids": [wnear.id(), usdc.id()],
+            "fee": 300
+        }))
+        .deposit(NearToken::from_millinear(1500))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    const ONE_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+    let _ = storage_deposit(&wnear, &bob).await?;
+    let _ = ft_transfer(&wnear, wnear.as_account(), &bob, ONE_NEAR).await?;
+    let _ = deposit_into_pool(&solver_registry, &bob, 0, &wnear, ONE_NEAR).await?;
+
+    // The owner can't value USDC against the pool while Bob holds all shares
+    let _ = storage_deposit(&usdc, &owner).await?;
+    let _ = ft_transfer(&usdc, usdc.as_account(), &owner, 1_000_000).await?;
+    let _ = deposit_into_pool(&solver_registry, &owner, 0, &usdc, 1_000_000).await?;
+    assert_eq!(
+        ft_balance_of(&usdc, owner.id()).await?,
+        1_000_000,
+        "A deposit of a token the pool doesn't hold should be refunded"
+    );
+
+    // Bob, who holds all shares, can add it
+    let _ = storage_deposit(&usdc, &bob).await?;
+    let _ = ft_transfer(&usdc, usdc.as_account(), &bob, 1_000_000).await?;
+    let _ = deposit_into_pool(&solver_registry, &bob, 0, &usdc, 1_000_000).await?;
+    assert_eq!(ft_balance_of(&usdc, bob.id()).await?, 0);
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    let shares: U128 = solver_registry
+        .view("get_pool_shares")
+        .args_json(json!({"pool_id": 0, "account_id": bob.id()}))
+        .await?
+        .json()?;
+    assert_eq!(shares, pool.shares_total_supply);
+
+    println!(
+        "Test passed: Only the holder of all shares can deposit a token the pool doesn't hold"
+    );
+
+    Ok(())
+}
+
+// Helper function to transfer a token for a batch deposit
+async fn batch_deposit_into_pool(
+    solver_registry: &near_workspaces::Contract,
+    user: &near_workspaces::Account,
+    pool_id: u32,
+    ft: &near_workspaces::Contract,
+    amount: u128,
+) -> Result<near_workspaces::result::ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = user
+        .call(ft.id(), "ft_transfer_call")
+        .args_json(json!({
+            "receiver_id": solver_registry.id(),
+            "amount": amount.to_string(),
+            "msg": json!({"BatchDepositIntoPool": {"pool_id": pool_id}}).to_string()
+        }))
+        .deposit(near_sdk::NearToken::from_yoctonear(1))
+        .gas(near_gas::NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    print_logs(&result);
+    Ok(result)
+}
+
+#[tokio::test]
+async fn test_batch_deposit() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for batch deposits...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    const ONE_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+    for account in [&alice, &bob] {
+        let _ = ft_transfer(&wnear, wnear.as_account(), account, 10 * ONE_NEAR).await?;
+        let _ = ft_transfer(&usdc, usdc.as_account(), account, 10_000_000).await?;
+    }
+
+    // The first token is held
+    let _ = batch_deposit_into_pool(&solver_registry, &alice, 0, &wnear, ONE_NEAR).await?;
+    assert_eq!(ft_balance_of(&wnear, alice.id()).await?, 9 * ONE_NEAR);
+    let pending: Option<PendingDeposit> = solver_registry
+        .view("get_pending_deposit")
+        .args_json(json!({"pool_id": 0, "account_id": alice.id()}))
+        .await?
+        .json()?;
+    let pending = pending.expect("wNEAR should be held");
+    assert_eq!(pending.amounts, vec![U128(ONE_NEAR), U128(0)]);
+
+    // The second token completes the deposit, which seeds the pool
+    let _ = batch_deposit_into_pool(&solver_registry, &alice, 0, &usdc, 2_000_000).await?;
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.amounts, vec![U128(ONE_NEAR), U128(2_000_000)]);
+    assert_eq!(pool.shares_total_supply, U128(ONE_NEAR));
+    let pending: Option<PendingDeposit> = solver_registry
+        .view("get_pending_deposit")
+        .args_json(json!({"pool_id": 0, "account_id": alice.id()}))
+        .await?
+        .json()?;
+    assert!(pending.is_none());
+
+    // Balanced deposits refund the held token beyond the pool ratio
+    let result = owner
+        .call(solver_registry.id(), "set_pool_deposit_mode")
+        .args_json(json!({"pool_id": 0, "deposit_mode": {"Balanced": {"tolerance_bps": 0}}}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let _ = batch_deposit_into_pool(&solver_registry, &bob, 0, &wnear, ONE_NEAR).await?;
+    let _ = batch_deposit_into_pool(&solver_registry, &bob, 0, &usdc, 1_000_000).await?;
+    assert_eq!(
+        ft_balance_of(&wnear, bob.id()).await?,
+        9 * ONE_NEAR + ONE_NEAR / 2
+    );
+    assert_eq!(ft_balance_of(&usdc, bob.id()).await?, 9_000_000);
+    let shares: U128 = solver_registry
+        .view("get_pool_shares")
+        .args_json(json!({"pool_id": 0, "account_id": bob.id()}))
+        .await?
+        .json()?;
+    assert_eq!(shares, U128(ONE_NEAR / 2));
+
+    // A held token can be reclaimed after the timeout
+    let _ = batch_deposit_into_pool(&solver_registry, &bob, 0, &usdc, 1_000_000).await?;
+    assert_eq!(ft_balance_of(&usdc, bob.id()).await?, 8_000_000);
+    let result = bob
+        .call(solver_registry.id(), "reclaim_pending_deposit")
+        .args_json(json!({"pool_id": 0}))
+        .gas(near_gas::NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Reclaiming before the timeout should fail"
+    );
+
+    let result = owner
+        .call(solver_registry.id(), "set_pending_deposit_timeout_ms")
+        .args_json(json!({"pending_deposit_timeout_ms": 0}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = bob
+        .call(solver_registry.id(), "reclaim_pending_deposit")
+        .args_json(json!({"pool_id": 0}))
+        .gas(near_gas::NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert_eq!(ft_balance_of(&usdc, bob.id()).await?, 9_000_000);
+
+    println!("Test passed: Batch deposits are held until all tokens arrive");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_batch_deposit_partial_refund() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for partially refunded batch deposits...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, _owner, alice, bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Alice seeds the pool with 1 NEAR and 2 USDC
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    const ONE_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+    for account in [&alice, &bob] {
+        let _ = ft_transfer(&wnear, wnear.as_account(), account, 10 * ONE_NEAR).await?;
+        let _ = ft_transfer(&usdc, usdc.as_account(), account, 10_000_000).await?;
+    }
+    let _ = batch_deposit_into_pool(&solver_registry, &alice, 0, &wnear, ONE_NEAR).await?;
+    let _ = batch_deposit_into_pool(&solver_registry, &alice, 0, &usdc, 2_000_000).await?;
+
+    // NEAR Intents refunds the USDC of Bob's batch but keeps the wNEAR
+    set_mock_intents_failures(
+        &mock_intents,
+        json!({"ft_refund_bps": 10_000, "ft_refund_token_id": usdc.id()}),
+    )
+    .await?;
+    let _ = batch_deposit_into_pool(&solver_registry, &bob, 0, &wnear, ONE_NEAR).await?;
+    let _ = batch_deposit_into_pool(&solver_registry, &bob, 0, &usdc, 2_000_000).await?;
+    assert_eq!(ft_balance_of(&wnear, bob.id()).await?, 9 * ONE_NEAR);
+    assert_eq!(
+        ft_balance_of(&usdc, bob.id()).await?,
+        10_000_000,
+        "The refunded USDC should be returned"
+    );
+
+    // The used wNEAR is credited at the pool ratio, as half of the pool value
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.amounts, vec![U128(2 * ONE_NEAR), U128(2_000_000)]);
+    let shares: U128 = solver_registry
+        .view("get_pool_shares")
+        .args_json(json!({"pool_id": 0, "account_id": bob.id()}))
+        .await?
+        .json()?;
+    assert_eq!(shares, U128(ONE_NEAR / 2));
+    assert_eq!(pool.shares_total_supply, U128(ONE_NEAR + ONE_NEAR / 2));
+    let deposit: Option<PoolDeposit> = solver_registry
+        .view("get_pool_deposit")
+        .args_json(json!({"pool_id": 0, "account_id": bob.id()}))
+        .await?
+        .json()?;
+    let deposit = deposit.expect("The used wNEAR should be recorded");
+    assert_eq!(deposit.amounts, vec![U128(ONE_NEAR), U128(0)]);
+
+    println!("Test passed: The used part of a partially refunded batch deposit is credited");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deposit_near_into_pool() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for native NEAR deposits...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create and seed a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    seed_pool(
+        &solver_registry,
+        &owner,
+        0,
+        &wnear,
+        &usdc,
+        1_000_000,
+        1_000_000,
+    )
+    .await?;
+
+    // NEAR can't be deposited until the wNEAR contract is configured
+    let result = alice
+        .call(solver_registry.id(), "deposit_near_into_pool")
+        .args_json(json!({"pool_id": 0}))
+        .deposit(NearToken::from_near(1))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "NEAR deposit should fail without a wNEAR contract"
+    );
+
+    // Only the owner can configure the wNEAR contract
+    let result = alice
+        .call(solver_registry.id(), "set_wnear_contract_id")
+        .args_json(json!({"wnear_contract_id": wnear.id()}))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Non-owner should not be able to set the wNEAR contract"
+    );
+    let result = owner
+        .call(solver_registry.id(), "set_wnear_contract_id")
+        .args_json(json!({"wnear_contract_id": wnear.id()}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Cap wNEAR deposits at 1 NEAR per account
+    let result = owner
+        .call(solver_registry.id(), "set_pool_token_limits")
+        .args_json(json!({
+            "pool_id": 0,
+            "token_id": wnear.id(),
+            "limits": {
+                "max_pool_amount": null,
+                "max_account_amount": NearToken::from_near(1).as_yoctonear().to_string(),
+                "min_deposit": null,
+            }
+        }))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Alice deposits 3 NEAR, of which 1 NEAR is wrapped into the pool and 2 NEAR are refunded
+    let balance_before = alice.view_account().await?.balance;
+    let result = alice
+        .call(solver_registry.id(), "deposit_near_into_pool")
+        .args_json(json!({"pool_id": 0}))
+        .deposit(NearToken::from_near(3))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert!(
+        result
+            .logs()
+            .iter()
+            .any(|log| log.contains("deposit_into_pool")),
+        "NEAR deposit should emit a deposit event"
+    );
+    let balance_after = alice.view_account().await?.balance;
+    let spent = balance_before.saturating_sub(balance_after);
+    assert!(
+        spent >= NearToken::from_near(1) && spent < NearToken::from_millinear(1100),
+        "Only the accepted NEAR should be spent, spent {}",
+        spent
+    );
+
+    // The wrapped NEAR is held by NEAR Intents and recorded as Alice's deposit
+    assert_eq!(
+        ft_balance_of(&wnear, mock_intents.id()).await?,
+        1_000_000 + NearToken::from_near(1).as_yoctonear()
+    );
+    assert_eq!(ft_balance_of(&wnear, solver_registry.id()).await?, 0);
+    let deposit: Option<PoolDeposit> = solver_registry
+        .view("get_pool_deposit")
+        .args_json(json!({"pool_id": 0, "account_id": alice.id()}))
+        .await?
+        .json()?;
+    let deposit = deposit.expect("Alice's deposit should be recorded");
+    assert_eq!(
+        deposit.amounts,
+        vec![U128(NearToken::from_near(1).as_yoctonear()), U128(0)]
+    );
+    let shares: U128 = solver_registry
+        .view("get_pool_shares")
+        .args_json(json!({"pool_id": 0, "account_id": alice.id()}))
+        .await?
+        .json()?;
+    assert!(shares.0 > 0, "Alice should receive pool shares");
+
+    println!("Test passed: NEAR is wrapped and deposited into the pool");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mt_deposit_into_pool() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for deposits of NEAR Intents balances...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool, seeded with 1 NEAR and 2 USDC
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    seed_pool(
+        &solver_registry,
+        &owner,
+        0,
+        &wnear,
+        &usdc,
+        1_000_000_000_000_000_000_000_000,
+        2_000_000,
+    )
+    .await?;
+
+    // Alice holds 3 USDC and 1 NEAR in NEAR Intents
+    let _ = ft_transfer(&usdc, usdc.as_account(), &alice, 3_000_000).await?;
+    let _ = ft_transfer(
+        &wnear,
+        wnear.as_account(),
+        &alice,
+        1_000_000_000_000_000_000_000_000,
+    )
+    .await?;
+    deposit_into_intents(&mock_intents, &alice, &usdc, 3_000_000).await?;
+    deposit_into_intents(
+        &mock_intents,
+        &alice,
+        &wnear,
+        1_000_000_000_000_000_000_000_000,
+    )
+    .await?;
+
+    // Only NEAR Intents can call the multi-token receiver
+    let result = alice
+        .call(solver_registry.id(), "mt_on_transfer")
+        .args_json(json!({
+            "sender_id": alice.id(),
+            "previous_owner_ids": [alice.id()],
+            "token_ids": [format!("nep141:{}", usdc.id())],
+            "amounts": ["1000000"],
+            "msg": json!({"DepositIntoPool": {"pool_id": 0}}).to_string(),
+        }))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Multi-token transfers should only be accepted from NEAR Intents"
+    );
+
+    // Alice deposits 1 USDC held in NEAR Intents, worth a quarter of the pool
+    let result = alice
+        .call(mock_intents.id(), "mt_transfer_call")
+        .args_json(json!({
+            "receiver_id": solver_registry.id(),
+            "token_id": format!("nep141:{}", usdc.id()),
+            "amount": "1000000",
+            "msg": json!({"DepositIntoPool": {"pool_id": 0}}).to_string(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    assert!(
+        result.logs().iter().any(|log| log
+            == &format!(
+                "Transfer 1000000 nep141:{} from {} to {}",
+                usdc.id(),
+                solver_registry.id(),
+                pool_account_id
+            )),
+        "The deposit should be forwarded to the pool account"
+    );
+    let shares: U128 = solver_registry
+        .view("get_pool_shares")
+        .args_json(json!({"pool_id": 0, "account_id": alice.id()}))
+        .await?
+        .json()?;
+    assert_eq!(shares, U128(500_000_000_000_000_000_000_000));
+
+    // Alice deposits both tokens at once
+    let result = alice
+        .call(mock_intents.id(), "mt_batch_transfer_call")
+        .args_json(json!({
+            "receiver_id": solver_registry.id(),
+            "token_ids": [format!("nep141:{}", usdc.id()), format!("nep141:{}", wnear.id())],
+            "amounts": ["2000000", "1000000000000000000000000"],
+            "msg": json!({"DepositIntoPool": {"pool_id": 0}}).to_string(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let deposit: Option<PoolDeposit> = solver_registry
+        .view("get_pool_deposit")
+        .args_json(json!({"pool_id": 0, "account_id": alice.id()}))
+        .await?
+        .json()?;
+    let deposit = deposit.expect("Alice's deposits should be recorded");
+    assert_eq!(
+        deposit.amounts,
+        vec![U128(1_000_000_000_000_000_000_000_000), U128(3_000_000)]
+    );
+
+    // The pool account holds the deposits in NEAR Intents
+    assert_eq!(
+        mt_balance_of(&mock_intents, &pool_account_id, &usdc).await?,
+        5_000_000
+    );
+    assert_eq!(mt_balance_of(&mock_intents, alice.id(), &usdc).await?, 0);
+
+    // Deposits into an unknown pool are refunded
+    let _ = ft_transfer(&usdc, usdc.as_account(), &alice, 1_000_000).await?;
+    deposit_into_intents(&mock_intents, &alice, &usdc, 1_000_000).await?;
+    let result = alice
+        .call(mock_intents.id(), "mt_transfer_call")
+        .args_json(json!({
+            "receiver_id": solver_registry.id(),
+            "token_id": format!("nep141:{}", usdc.id()),
+            "amount": "1000000",
+            "msg": json!({"DepositIntoPool": {"pool_id": 5}}).to_string(),
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    let used: Vec<U128> = result.json()?;
+    assert_eq!(used, vec![U128(0)], "Deposit should be refunded");
+    assert_eq!(
+        mt_balance_of(&mock_intents, alice.id(), &usdc).await?,
+        1_000_000
+    );
+
+    // Alice withdraws the refunded balance from NEAR Intents
+    let result = alice
+        .call(mock_intents.id(), "ft_withdraw")
+        .args_json(json!({
+            "token": usdc.id(),
+            "receiver_id": alice.id(),
+            "amount": "1000000",
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert_eq!(mt_balance_of(&mock_intents, alice.id(), &usdc).await?, 0);
+    assert_eq!(ft_balance_of(&usdc, alice.id()).await?, 1_000_000);
+
+    println!("Test passed: NEAR Intents balances are deposited into the pool");
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use near_gas::NearGas;
+use near_sdk::NearToken;
+use serde_json::json;
+
+mod common;
+
+use common::constants::*;
+use common::utils::*;
+
+#[tokio::test]
+async fn test_worker_key_signs_pool_intents() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for intents signed by the worker key...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool, seeded with 1 NEAR and 2 USDC, and register Alice as its worker
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    seed_pool(
+        &solver_registry,
+        &owner,
+        0,
+        &wnear,
+        &usdc,
+        1_000_000_000_000_000_000_000_000,
+        2_000_000,
+    )
+    .await?;
+    approve_compose_hash(&owner, &solver_registry).await?;
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+
+    // Bob holds 1 USDC in NEAR Intents and signs with his own key
+    let _ = ft_transfer(&usdc, usdc.as_account(), &bob, 1_000_000).await?;
+    deposit_into_intents(&mock_intents, &bob, &usdc, 1_000_000).await?;
+    let result = bob
+        .call(mock_intents.id(), "add_public_key")
+        .args_json(json!({"public_key": bob.secret_key().public_key().to_string()}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // The pool sells 0.1 NEAR to Bob for 1 USDC
+    let wnear_token_id = format!("nep141:{}", wnear.id());
+    let usdc_token_id = format!("nep141:{}", usdc.id());
+    let pool_payload = json!({
+        "signer_id": pool_account_id,
+        "intents": [{
+            "intent": "token_diff",
+            "diff": {
+                wnear_token_id.clone(): "-100000000000000000000000",
+                usdc_token_id.clone(): "1000000",
+            },
+        }],
+    });
+    let bob_payload = json!({
+        "signer_id": bob.id(),
+        "intents": [{
+            "intent": "token_diff",
+            "diff": {
+                wnear_token_id.clone(): "100000000000000000000000",
+                usdc_token_id.clone(): "-1000000",
+            },
+        }],
+    });
+
+    // A key that isn't authorized for the pool account can't trade the pool funds
+    let result = execute_intents(
+        &mock_intents,
+        &bob,
+        vec![
+            sign_intent_payload(SECRET_KEY_BOB, pool_payload.clone()),
+            sign_intent_payload(SECRET_KEY_BOB, bob_payload.clone()),
+        ],
+    )
+    .await?;
+    assert!(
+        !result.is_success(),
+        "Intents signed by an unauthorized key should fail"
+    );
+
+    // Alice's registered key trades the pool funds
+    let result = execute_intents(
+        &mock_intents,
+        &bob,
+        vec![
+            sign_intent_payload(SECRET_KEY_ALICE, pool_payload.clone()),
+            sign_intent_payload(SECRET_KEY_BOB, bob_payload.clone()),
+        ],
+    )
+    .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert_eq!(
+        mt_balance_of(&mock_intents, &pool_account_id, &wnear).await?,
+        900_000_000_000_000_000_000_000
+    );
+    assert_eq!(
+        mt_balance_of(&mock_intents, &pool_account_id, &usdc).await?,
+        3_000_000
+    );
+    assert_eq!(
+        mt_balance_of(&mock_intents, bob.id(), &wnear).await?,
+        100_000_000_000_000_000_000_000
+    );
+    assert_eq!(mt_balance_of(&mock_intents, bob.id(), &usdc).await?, 0);
+
+    // Winding down the pool removes Alice's key, so she can no longer trade the pool funds
+    let result = owner
+        .call(solver_registry.id(), "wind_down_pool")
+        .args_json(json!({"pool_id": 0}))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let bob_payload = json!({
+        "signer_id": bob.id(),
+        "intents": [{
+            "intent": "token_diff",
+            "diff": {
+                wnear_token_id.clone(): "-100000000000000000000000",
+                usdc_token_id.clone(): "1000000",
+            },
+        }],
+    });
+    let pool_payload = json!({
+        "signer_id": pool_account_id,
+        "intents": [{
+            "intent": "token_diff",
+            "diff": {
+                wnear_token_id: "100000000000000000000000",
+                usdc_token_id: "-1000000",
+            },
+        }],
+    });
+    let result = execute_intents(
+        &mock_intents,
+        &bob,
+        vec![
+            sign_intent_payload(SECRET_KEY_ALICE, pool_payload),
+            sign_intent_payload(SECRET_KEY_BOB, bob_payload),
+        ],
+    )
+    .await?;
+    assert!(
+        !result.is_success(),
+        "Intents signed by a removed key should fail"
+    );
+    assert_eq!(
+        mt_balance_of(&mock_intents, &pool_account_id, &usdc).await?,
+        3_000_000
+    );
+    assert_eq!(mt_balance_of(&mock_intents, bob.id(), &usdc).await?, 0);
+
+    println!("Test passed: Only the registered worker key can sign intents for the pool");
+
+    Ok(())
+}
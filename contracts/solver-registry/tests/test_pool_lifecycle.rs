@@ -0,0 +1,180 @@
+use near_gas::NearGas;
+use near_sdk::NearToken;
+use serde_json::json;
+use solver_registry::pool::PoolStatus;
+
+mod common;
+
+use common::utils::*;
+
+#[tokio::test]
+async fn test_pool_lifecycle() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for pool lifecycle...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Bob creates a liquidity pool
+    let result = bob
+        .call(solver_registry.id(), "create_liquidity_pool")
+        .args_json(json!({
+            "token_ids": [wnear.id(), usdc.id()],
+            "fee": 300
+        }))
+        .deposit(NearToken::from_millinear(1500))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    seed_pool(
+        &solver_registry,
+        &bob,
+        0,
+        &wnear,
+        &usdc,
+        1_000_000,
+        1_000_000,
+    )
+    .await?;
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.status, PoolStatus::Active);
+    assert_eq!(
+        pool.creator_id.as_ref().map(|id| id.as_str()),
+        Some(bob.id().as_str())
+    );
+
+    // Only the owner or the pool creator can change the status
+    let result = alice
+        .call(solver_registry.id(), "close_pool_deposits")
+        .args_json(json!({"pool_id": 0}))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Closing deposits by another account should fail"
+    );
+
+    // The pool creator closes deposits
+    let result = bob
+        .call(solver_registry.id(), "close_pool_deposits")
+        .args_json(json!({"pool_id": 0}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.status, PoolStatus::DepositsClosed);
+
+    // Deposits are refunded
+    let _ = ft_transfer(&usdc, usdc.as_account(), &alice, 10_000_000).await?;
+    let _ = deposit_into_pool(&solver_registry, &alice, 0, &usdc, 1_000_000).await?;
+    assert_eq!(
+        ft_balance_of(&usdc, alice.id()).await?,
+        10_000_000,
+        "Deposit into a pool with closed deposits should be refunded"
+    );
+
+    // Workers can still register
+    approve_compose_hash(&owner, &solver_registry).await?;
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed while deposits are closed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    assert_eq!(
+        get_pool_public_keys(&mock_intents, &pool_account_id)
+            .await?
+            .len(),
+        1
+    );
+
+    // The owner reopens deposits
+    let result = owner
+        .call(solver_registry.id(), "reopen_pool_deposits")
+        .args_json(json!({"pool_id": 0}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let _ = deposit_into_pool(&solver_registry, &alice, 0, &usdc, 1_000_000).await?;
+    assert_eq!(ft_balance_of(&usdc, alice.id()).await?, 9_000_000);
+
+    // Closing a pool that is not winding down is rejected
+    let result = owner
+        .call(solver_registry.id(), "close_pool")
+        .args_json(json!({"pool_id": 0}))
+        .transact()
+        .await?;
+    assert!(!result.is_success(), "Closing an active pool should fail");
+
+    // Winding down revokes the worker key
+    let result = owner
+        .call(solver_registry.id(), "wind_down_pool")
+        .args_json(json!({"pool_id": 0}))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.status, PoolStatus::WindingDown);
+    assert!(pool.worker_id.is_none(), "Worker should be removed");
+    assert!(get_worker_info(&solver_registry, &alice).await?.is_none());
+    assert!(
+        get_pool_public_keys(&mock_intents, &pool_account_id)
+            .await?
+            .is_empty(),
+        "Worker key should be revoked"
+    );
+
+    // Neither deposits nor registrations are accepted
+    let _ = deposit_into_pool(&solver_registry, &alice, 0, &usdc, 1_000_000).await?;
+    assert_eq!(ft_balance_of(&usdc, alice.id()).await?, 9_000_000);
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        !result.is_success(),
+        "Worker registration should fail for a winding down pool"
+    );
+
+    // Close the pool
+    let result = bob
+        .call(solver_registry.id(), "close_pool")
+        .args_json(json!({"pool_id": 0}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.status, PoolStatus::Closed);
+
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        !result.is_success(),
+        "Worker registration should fail for a closed pool"
+    );
+
+    println!("Test passed: Pool lifecycle transitions are enforced");
+
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use near_gas::NearGas;
+use near_sdk::NearToken;
+use serde_json::json;
+
+mod common;
+
+use common::utils::*;
+
+#[tokio::test]
+async fn test_pool_operator() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for pool operator...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Bob creates a liquidity pool and becomes its operator
+    let result = bob
+        .call(solver_registry.id(), "create_liquidity_pool")
+        .args_json(json!({
+            "token_ids": [wnear.id(), usdc.id()],
+            "fee": 300
+        }))
+        .deposit(NearToken::from_millinear(1500))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(
+        pool.operator_id.as_ref().map(|id| id.as_str()),
+        Some(bob.id().as_str())
+    );
+
+    // Fee changes take effect after the delay
+    let result = owner
+        .call(solver_registry.id(), "set_pool_fee_delay_ms")
+        .args_json(json!({"pool_fee_delay_ms": 3000}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    let result = alice
+        .call(solver_registry.id(), "set_pool_fee")
+        .args_json(json!({"pool_id": 0, "fee": 350}))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Setting the fee by another account should fail"
+    );
+
+    let result = bob
+        .call(solver_registry.id(), "set_pool_fee")
+        .args_json(json!({"pool_id": 0, "fee": 500}))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Changing the fee by more than 1% should fail"
+    );
+
+    let result = bob
+        .call(solver_registry.id(), "set_pool_fee")
+        .args_json(json!({"pool_id": 0, "fee": 350}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.fee, 300);
+    assert_eq!(
+        pool.pending_fee.as_ref().map(|pending| pending.fee),
+        Some(350)
+    );
+
+    let result = bob
+        .call(solver_registry.id(), "apply_pool_fee")
+        .args_json(json!({"pool_id": 0}))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Applying the fee before the delay should fail"
+    );
+
+    tokio::time::sleep(tokio::time::Duration::from_secs(4)).await;
+    let result = bob
+        .call(solver_registry.id(), "apply_pool_fee")
+        .args_json(json!({"pool_id": 0}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.fee, 350);
+    assert!(pool.pending_fee.is_none());
+
+    // The operator sets the pool ping timeout and compose hash allowlist
+    let result = bob
+        .call(solver_registry.id(), "set_pool_worker_ping_timeout_ms")
+        .args_json(json!({"pool_id": 0, "worker_ping_timeout_ms": 60_000}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    approve_compose_hash(&owner, &solver_registry).await?;
+    let result = bob
+        .call(solver_registry.id(), "set_pool_compose_hashes")
+        .args_json(json!({"pool_id": 0, "compose_hashes": [common::constants::COMPOSE_HASH]}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.worker_ping_timeout_ms, Some(60_000));
+    assert_eq!(
+        pool.allowed_compose_hashes,
+        Some(vec![common::constants::COMPOSE_HASH.to_string()])
+    );
+
+    // Bob hands the pool over to Alice
+    let result = bob
+        .call(solver_registry.id(), "transfer_pool_operator")
+        .args_json(json!({"pool_id": 0, "new_operator_id": alice.id()}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(
+        pool.operator_id.as_ref().map(|id| id.as_str()),
+        Some(alice.id().as_str())
+    );
+    assert_eq!(
+        pool.creator_id.as_ref().map(|id| id.as_str()),
+        Some(bob.id().as_str())
+    );
+
+    let result = bob
+        .call(solver_registry.id(), "set_pool_worker_ping_timeout_ms")
+        .args_json(json!({"pool_id": 0, "worker_ping_timeout_ms": null}))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "The previous operator should no longer manage the pool"
+    );
+    let result = alice
+        .call(solver_registry.id(), "set_pool_worker_ping_timeout_ms")
+        .args_json(json!({"pool_id": 0, "worker_ping_timeout_ms": null}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    println!("Test passed: Pool operator can manage the pool");
+
+    Ok(())
+}
@@ -394,3 +394,195 @@ async fn test_worker_registration_edge_cases() -> Result<(), Box<dyn std::error:
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_register_worker_key_add_failure() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for failed worker key registration...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    approve_compose_hash(&owner, &solver_registry).await?;
+
+    // NEAR Intents rejects the worker key
+    set_mock_intents_failures(&mock_intents, json!({"add_public_key": true})).await?;
+
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(result.is_success(), "Registration should not fail");
+    assert!(
+        result
+            .logs()
+            .iter()
+            .any(|log| log.contains("worker_registration_failed")),
+        "Failed registration should emit an event"
+    );
+    assert!(
+        result.logs().iter().any(|log| is_refund_event(log, &alice)),
+        "The attached deposit should be refunded"
+    );
+    let registered: bool = result.json()?;
+    assert!(!registered, "Registration should report the failure");
+
+    // The worker is not registered
+    let worker = get_worker_info(&solver_registry, &alice).await?;
+    assert!(worker.is_none(), "Alice should not be registered");
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert!(pool.worker_id.is_none());
+
+    // Registration succeeds once NEAR Intents accepts the key
+    set_mock_intents_failures(&mock_intents, json!({})).await?;
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    let registered: bool = result.json()?;
+    assert!(registered, "Registration should succeed");
+    let worker = get_worker_info(&solver_registry, &alice).await?;
+    assert!(worker.is_some(), "Alice should be registered");
+
+    println!("Test passed: Failed worker key registration is reported and refunded");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_worker_replacement_key_add_failure() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for failed key registration of a replacement worker...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 5 * 1000).await?;
+
+    // Create a liquidity pool and register Alice as its worker
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    approve_compose_hash(&owner, &solver_registry).await?;
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Alice times out. Her key is removed, but NEAR Intents rejects Bob's key.
+    wait_for_worker_timeout(5).await;
+    set_mock_intents_failures(&mock_intents, json!({"add_public_key": true})).await?;
+    let result = register_worker_bob(&bob, &solver_registry, 0).await?;
+    assert!(result.is_success(), "Registration should not fail");
+    assert!(
+        result
+            .logs()
+            .iter()
+            .any(|log| log.contains("worker_registration_failed")),
+        "Failed registration should emit an event"
+    );
+    assert!(
+        result.logs().iter().any(|log| is_refund_event(log, &bob)),
+        "The attached deposit should be refunded"
+    );
+    let registered: bool = result.json()?;
+    assert!(!registered, "Registration should report the failure");
+
+    // The pool has no worker left
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert!(
+        pool.worker_id.is_none(),
+        "Alice should no longer be the pool worker"
+    );
+    assert!(pool.pending_worker_key.is_none());
+    assert!(get_worker_info(&solver_registry, &alice).await?.is_none());
+    assert!(get_worker_info(&solver_registry, &bob).await?.is_none());
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert!(public_keys.is_empty());
+
+    // Bob registers once NEAR Intents accepts his key
+    set_mock_intents_failures(&mock_intents, json!({})).await?;
+    let result = register_worker_bob(&bob, &solver_registry, 0).await?;
+    let registered: bool = result.json()?;
+    assert!(registered, "Registration should succeed");
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.worker_id.as_ref(), Some(bob.id()));
+
+    println!("Test passed: A failed replacement leaves the pool without a worker");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ping_events_on_liveness_changes() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for logging pings on liveness changes...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 5 * 1000).await?;
+
+    // Create a liquidity pool and register Alice as its worker
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    approve_compose_hash(&owner, &solver_registry).await?;
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Only the owner can change which pings are logged
+    let result = alice
+        .call(solver_registry.id(), "set_ping_events")
+        .args_json(json!({"ping_events": "LivenessChanges"}))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Non-owner should not be able to change the ping events"
+    );
+    let result = owner
+        .call(solver_registry.id(), "set_ping_events")
+        .args_json(json!({"ping_events": "LivenessChanges"}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    let ping_events: String = solver_registry.view("get_ping_events").await?.json()?;
+    assert_eq!(ping_events, "LivenessChanges");
+
+    let is_pinged_event = |log: &str| log.contains(r#""event":"worker_pinged""#);
+
+    // Pings of an active worker aren't logged
+    let result = ping_worker(&alice, &solver_registry).await?;
+    assert!(result.is_success(), "Alice should be able to ping");
+    assert!(!result.logs().iter().any(|log| is_pinged_event(log)));
+
+    // The first ping after a timeout is logged
+    wait_for_worker_timeout(5).await;
+    let result = ping_worker(&alice, &solver_registry).await?;
+    assert!(result.is_success(), "Alice should be able to ping");
+    assert!(result.logs().iter().any(|log| is_pinged_event(log)));
+    let result = ping_worker(&alice, &solver_registry).await?;
+    assert!(!result.logs().iter().any(|log| is_pinged_event(log)));
+
+    // Every ping is logged again
+    let result = owner
+        .call(solver_registry.id(), "set_ping_events")
+        .args_json(json!({"ping_events": "All"}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = ping_worker(&alice, &solver_registry).await?;
+    assert!(result.logs().iter().any(|log| is_pinged_event(log)));
+
+    println!("Test passed: Pings are logged when the worker liveness changes");
+
+    Ok(())
+}
@@ -0,0 +1,596 @@
+use near_gas::NearGas;
+use near_sdk::{AccountId, NearToken, PublicKey};
+use near_workspaces::types::CryptoHash;
+use near_workspaces::ContractState;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use solver_registry::types::{ComposeHashInfo, StagedCodeInfo};
+
+mod common;
+
+use common::constants::*;
+use common::utils::*;
+
+#[tokio::test]
+async fn test_upgrade_from_v1_preserves_state() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for upgrading from v0.1.0...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup tokens and accounts
+    let (wnear, usdc) = create_test_tokens(&sandbox).await?;
+    let (owner, alice, bob) = create_test_accounts(&sandbox).await?;
+    let mock_intents = deploy_mock_intents(&sandbox).await?;
+
+    // Deploy the old version of the solver registry
+    println!("Deploying Solver Registry v0.1.0...");
+    let solver_registry = deploy_solver_registry_from_wasm(
+        &sandbox,
+        SOLVER_REGISTRY_V1_CONTRACT_WASM,
+        &mock_intents,
+        &owner,
+        5 * 1000,
+    )
+    .await?;
+
+    // Populate the old state: a pool, an approved compose hash and a registered worker
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    approve_compose_hash(&owner, &solver_registry).await?;
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Upgrade to the new version
+    println!("Upgrading Solver Registry...");
+    let new_wasm = std::fs::read(SOLVER_REGISTRY_CONTRACT_WASM).expect("Contract wasm not found");
+    let result = owner
+        .call(solver_registry.id(), "upgrade")
+        .args(new_wasm)
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "Upgrade should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    let result = solver_registry.view("get_version").await?;
+    let version: String = serde_json::from_slice(&result.result).unwrap();
+    assert_eq!(version, env!("CARGO_PKG_VERSION"));
+
+    // Pools survive the migration
+    let result = solver_registry.view("get_pool_len").await?;
+    let pool_len: u32 = serde_json::from_slice(&result.result).unwrap();
+    assert_eq!(pool_len, 1, "Pool should survive the migration");
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.token_ids, vec![wnear.id().clone(), usdc.id().clone()]);
+    assert_eq!(pool.fee, 300);
+    assert_eq!(pool.worker_id.as_ref(), Some(alice.id()));
+    assert!(pool.allowed_compose_hashes.is_none());
+
+    // Workers survive the migration
+    let worker = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should still be registered as a worker");
+    assert_eq!(worker.pool_id, 0);
+    assert_eq!(worker.checksum, CHECKSUM_ALICE);
+    assert_eq!(worker.compose_hash, COMPOSE_HASH);
+
+    // Approved compose hashes are migrated with metadata
+    let result = solver_registry
+        .view("get_compose_hash")
+        .args_json(json!({"compose_hash": COMPOSE_HASH}))
+        .await?;
+    let info: Option<ComposeHashInfo> = serde_json::from_slice(&result.result).unwrap();
+    let info = info.expect("Compose hash should still be approved");
+    assert_eq!(&info.approved_by, owner.id());
+
+    // The migrated worker keeps working
+    let result = ping_worker(&alice, &solver_registry).await?;
+    assert!(
+        result.is_success(),
+        "Migrated worker should be able to ping: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // The v0.1.0 vault can't be upgraded, so it's skipped
+    let result = owner
+        .call(solver_registry.id(), "upgrade_pool_vaults")
+        .args_json(json!({"from": 0, "limit": 5}))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    let num_upgrades: u32 = result.json()?;
+    assert_eq!(num_upgrades, 0, "The v0.1.0 vault should be skipped");
+
+    // Bob replaces Alice once she times out, through the v0.1.0 vault interface
+    wait_for_worker_timeout(5).await;
+    let result = register_worker_bob(&bob, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker replacement should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.worker_id.as_ref(), Some(bob.id()));
+    let bob_worker = get_worker_info(&solver_registry, &bob)
+        .await?
+        .expect("Bob should be registered as a worker");
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert_eq!(public_keys.len(), 1, "Alice's key should be removed");
+    assert!(public_keys.contains(&bob_worker.public_key));
+
+    // Winding down revokes Bob's key through the v0.1.0 vault interface
+    let result = owner
+        .call(solver_registry.id(), "wind_down_pool")
+        .args_json(json!({"pool_id": 0}))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "Winding down should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert!(public_keys.is_empty(), "Bob's key should be revoked");
+
+    println!("Test passed: State survives the upgrade from v0.1.0");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_staged_code_upgrade() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for staged code upgrade...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    let code = std::fs::read(SOLVER_REGISTRY_CONTRACT_WASM).expect("Contract wasm not found");
+    let code_hash = hex::encode(Sha256::digest(&code));
+
+    // Only the owner can stage code
+    let result = alice
+        .call(solver_registry.id(), "stage_code")
+        .args(code.clone())
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(!result.is_success(), "Non-owner should not stage code");
+
+    // Set an upgrade delay and stage the code
+    let result = owner
+        .call(solver_registry.id(), "set_upgrade_delay_ms")
+        .args_json(json!({"upgrade_delay_ms": 10_000}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = owner
+        .call(solver_registry.id(), "stage_code")
+        .args(code.clone())
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "Staging code should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // The staged hash matches the local build
+    let result = solver_registry.view("get_staged_code").await?;
+    let staged: Option<StagedCodeInfo> = serde_json::from_slice(&result.result).unwrap();
+    let staged = staged.expect("Code should be staged");
+    assert_eq!(staged.code_hash, code_hash);
+    assert_eq!(staged.deployable_at_ms, staged.staged_at_ms + 10_000);
+
+    // Removing the delay is only scheduled, and doesn't apply to the staged code
+    let result = owner
+        .call(solver_registry.id(), "set_upgrade_delay_ms")
+        .args_json(json!({"upgrade_delay_ms": 0}))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = solver_registry.view("get_upgrade_delay_ms").await?;
+    let upgrade_delay_ms: u64 = serde_json::from_slice(&result.result).unwrap();
+    assert_eq!(
+        upgrade_delay_ms, 10_000,
+        "The reduction should be scheduled"
+    );
+    let result = owner
+        .call(solver_registry.id(), "apply_upgrade_delay_ms")
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "The reduction should not apply before the current delay"
+    );
+
+    // Deployment is rejected before the delay passes
+    let result = owner
+        .call(solver_registry.id(), "deploy_staged_code")
+        .args_json(json!({"code_hash": code_hash}))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Deployment should fail before the upgrade delay"
+    );
+
+    wait_for_worker_timeout(10).await;
+
+    // Deployment is rejected with a mismatching hash
+    let result = owner
+        .call(solver_registry.id(), "deploy_staged_code")
+        .args_json(json!({"code_hash": "00".repeat(32)}))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Deployment should fail with a mismatching code hash"
+    );
+
+    // Deploy the staged code
+    let result = owner
+        .call(solver_registry.id(), "deploy_staged_code")
+        .args_json(json!({"code_hash": code_hash}))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "Deployment should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert!(
+        result
+            .logs()
+            .iter()
+            .any(|log| log.contains("code_deployed")),
+        "CodeDeployed should be emitted after the deployment"
+    );
+
+    // The staged code is consumed and the state is intact
+    let result = solver_registry.view("get_staged_code").await?;
+    let staged: Option<StagedCodeInfo> = serde_json::from_slice(&result.result).unwrap();
+    assert!(staged.is_none(), "Staged code should be consumed");
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.token_ids, vec![wnear.id().clone(), usdc.id().clone()]);
+
+    // The delay reduction applies once the previous delay has passed
+    let result = owner
+        .call(solver_registry.id(), "apply_upgrade_delay_ms")
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = solver_registry.view("get_upgrade_delay_ms").await?;
+    let upgrade_delay_ms: u64 = serde_json::from_slice(&result.result).unwrap();
+    assert_eq!(upgrade_delay_ms, 0);
+
+    println!("Test passed: Staged code is deployed after hash verification");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pool_vault_interface() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for the pool vault interface...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, _owner, alice, _bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // The pool account runs the vault code built from the current sources
+    let code = std::fs::read(INTENTS_VAULT_CONTRACT_WASM).expect("Contract wasm not found");
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    let pool_account = sandbox.view_account(&pool_account_id).await?;
+    assert_eq!(
+        pool_account.contract_state,
+        ContractState::LocalHash(CryptoHash(Sha256::digest(&code).into()))
+    );
+
+    let result = sandbox.view(&pool_account_id, "get_version").await?;
+    let vault_version: String = serde_json::from_slice(&result.result).unwrap();
+    assert_eq!(vault_version, "0.2.0");
+    let registry_id: AccountId = sandbox
+        .view(&pool_account_id, "get_registry_id")
+        .await?
+        .json()?;
+    assert_eq!(&registry_id, solver_registry.id());
+    let intents_contract_id: AccountId = sandbox
+        .view(&pool_account_id, "get_intents_contract_id")
+        .await?
+        .json()?;
+    assert_eq!(&intents_contract_id, mock_intents.id());
+
+    // The vault was initialized through `new`, and only the registry can migrate or upgrade it
+    let result = alice
+        .call(&pool_account_id, "new")
+        .args_json(json!({"intents_contract_id": mock_intents.id()}))
+        .transact()
+        .await?;
+    let error = format!("{:?}", result.into_result().unwrap_err());
+    assert!(
+        error.contains("The contract has already been initialized"),
+        "Unexpected error: {error}"
+    );
+
+    let result = alice
+        .call(&pool_account_id, "migrate")
+        .args_json(json!({"intents_contract_id": mock_intents.id(), "public_keys": []}))
+        .transact()
+        .await?;
+    let error = format!("{:?}", result.into_result().unwrap_err());
+    assert!(
+        error.contains("Only the registry can perform this action"),
+        "Unexpected error: {error}"
+    );
+
+    let result = alice
+        .call(&pool_account_id, "upgrade")
+        .args(code)
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    let error = format!("{:?}", result.into_result().unwrap_err());
+    assert!(
+        error.contains("Only the registry can perform this action"),
+        "Unexpected error: {error}"
+    );
+
+    println!("Test passed: Pool vaults expose the current interface");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_upgrade_pool_vaults() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for pool vault upgrades...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Register a worker, whose key must survive the vault upgrade
+    approve_compose_hash(&owner, &solver_registry).await?;
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "Worker registration should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let alice_worker = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should be registered as a worker");
+
+    // New pools record the hash of the embedded vault code
+    let result = solver_registry.view("get_vault_code_hash").await?;
+    let embedded_code_hash: String = serde_json::from_slice(&result.result).unwrap();
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.vault_code_hash, Some(embedded_code_hash.clone()));
+
+    // Stage new vault code. A custom section makes its hash differ from the embedded code.
+    let mut code = std::fs::read(INTENTS_VAULT_CONTRACT_WASM).expect("Contract wasm not found");
+    code.extend_from_slice(&[0x00, 0x05, 0x04, b't', b'e', b's', b't']);
+    let code_hash = hex::encode(Sha256::digest(&code));
+    assert_ne!(code_hash, embedded_code_hash);
+
+    let result = owner
+        .call(solver_registry.id(), "stage_vault_code")
+        .args(code)
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "Staging vault code should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = solver_registry.view("get_vault_code_hash").await?;
+    let current_code_hash: String = serde_json::from_slice(&result.result).unwrap();
+    assert_eq!(current_code_hash, code_hash);
+
+    // Push the staged code to the pool vaults
+    let result = owner
+        .call(solver_registry.id(), "upgrade_pool_vaults")
+        .args_json(json!({"from": 0, "limit": 5}))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "Vault upgrade should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    // The upgrades of the batch are logged as one event listing both pools
+    let upgraded_logs: Vec<serde_json::Value> = result
+        .logs()
+        .iter()
+        .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+        .map(|log| serde_json::from_str::<serde_json::Value>(log).unwrap())
+        .filter(|event| event["event"] == "pool_vault_upgraded")
+        .collect();
+    let num_upgrades: u32 = result.json()?;
+    assert_eq!(num_upgrades, 2);
+    assert_eq!(upgraded_logs.len(), 1);
+    assert_eq!(
+        upgraded_logs[0]["data"],
+        json!([
+            {"pool_id": 0, "code_hash": code_hash},
+            {"pool_id": 1, "code_hash": code_hash},
+        ])
+    );
+
+    for pool_id in 0..2 {
+        let pool = get_pool_info(&solver_registry, pool_id).await?;
+        assert_eq!(pool.vault_code_hash, Some(code_hash.clone()));
+    }
+
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    let result = sandbox.view(&pool_account_id, "get_version").await?;
+    let vault_version: String = serde_json::from_slice(&result.result).unwrap();
+    assert_eq!(vault_version, "0.2.0");
+
+    // The vault state survives the migration
+    let registry_id: AccountId = sandbox
+        .view(&pool_account_id, "get_registry_id")
+        .await?
+        .json()?;
+    assert_eq!(&registry_id, solver_registry.id());
+    let intents_contract_id: AccountId = sandbox
+        .view(&pool_account_id, "get_intents_contract_id")
+        .await?
+        .json()?;
+    assert_eq!(&intents_contract_id, mock_intents.id());
+    let authorized_keys: Vec<PublicKey> = sandbox
+        .view(&pool_account_id, "get_authorized_keys")
+        .await?
+        .json()?;
+    assert_eq!(authorized_keys, vec![alice_worker.public_key]);
+
+    // Up-to-date vaults are skipped
+    let result = owner
+        .call(solver_registry.id(), "upgrade_pool_vaults")
+        .args_json(json!({"from": 0, "limit": 5}))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    let num_upgrades: u32 = result.json()?;
+    assert_eq!(num_upgrades, 0);
+
+    println!("Test passed: Pool vaults are upgraded from the registry");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_upgrade_pool_vaults_with_global_contract() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for pool vault upgrades with the vault global contract...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, _alice, _bob, _mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Publish the vault global contract, so the pool account is only funded with 0.1 NEAR
+    let result = owner
+        .call(solver_registry.id(), "publish_vault_global_contract")
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "Publishing the vault global contract should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = solver_registry.view("get_vault_global_code_hash").await?;
+    let global_code_hash: Option<String> = serde_json::from_slice(&result.result).unwrap();
+    let global_code_hash = global_code_hash.expect("Vault global contract should be published");
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+
+    // Staged vault code isn't used until it's published
+    let mut code = std::fs::read(INTENTS_VAULT_CONTRACT_WASM).expect("Contract wasm not found");
+    code.extend_from_slice(&[0x00, 0x05, 0x04, b't', b'e', b's', b't']);
+    let code_digest = CryptoHash(Sha256::digest(&code).into());
+    let code_hash = hex::encode(code_digest.0);
+    let result = owner
+        .call(solver_registry.id(), "stage_vault_code")
+        .args(code)
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "Staging vault code should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = solver_registry.view("get_vault_global_code_hash").await?;
+    let current_global_code_hash: Option<String> = serde_json::from_slice(&result.result).unwrap();
+    assert_eq!(current_global_code_hash, Some(global_code_hash.clone()));
+    let result = solver_registry.view("get_vault_code_hash").await?;
+    let current_code_hash: String = serde_json::from_slice(&result.result).unwrap();
+    assert_eq!(current_code_hash, global_code_hash);
+
+    let result = owner
+        .call(solver_registry.id(), "upgrade_pool_vaults")
+        .args_json(json!({"from": 0, "limit": 5}))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    let num_upgrades: u32 = result.json()?;
+    assert_eq!(
+        num_upgrades, 0,
+        "The vault already runs the global contract"
+    );
+
+    // Publish the staged code and switch the vault to it
+    let result = owner
+        .call(solver_registry.id(), "publish_vault_global_contract")
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "Publishing the staged vault code should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = owner
+        .call(solver_registry.id(), "upgrade_pool_vaults")
+        .args_json(json!({"from": 0, "limit": 5}))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "Vault upgrade should succeed: {:#?}",
+        result.into_result().unwrap_err()
+    );
+    let num_upgrades: u32 = result.json()?;
+    assert_eq!(num_upgrades, 1);
+
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert_eq!(pool.vault_code_hash, Some(code_hash));
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    assert_eq!(
+        sandbox.view_account(&pool_account_id).await?.contract_state,
+        ContractState::GlobalHash(code_digest)
+    );
+    let registry_id: AccountId = sandbox
+        .view(&pool_account_id, "get_registry_id")
+        .await?
+        .json()?;
+    assert_eq!(&registry_id, solver_registry.id());
+
+    println!("Test passed: Pool vaults are upgraded through the vault global contract");
+
+    Ok(())
+}
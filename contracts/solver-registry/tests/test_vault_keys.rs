@@ -0,0 +1,256 @@
+use near_gas::NearGas;
+use near_sdk::{NearToken, PublicKey};
+use near_workspaces::types::{KeyType, SecretKey};
+use serde_json::json;
+
+mod common;
+
+use common::utils::*;
+
+#[tokio::test]
+async fn test_vault_authorized_keys() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for vault authorized keys...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 5 * 1000).await?;
+
+    // Create a liquidity pool
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    approve_compose_hash(&owner, &solver_registry).await?;
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+
+    // Only allow a single key in the pool vault
+    let result = alice
+        .call(solver_registry.id(), "set_pool_vault_single_key_policy")
+        .args_json(json!({"pool_id": 0, "enabled": true}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Non-operator should not be able to set the vault key policy"
+    );
+    let result = owner
+        .call(solver_registry.id(), "set_pool_vault_single_key_policy")
+        .args_json(json!({"pool_id": 0, "enabled": true}))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let single_key_policy: bool = sandbox
+        .view(&pool_account_id, "get_single_key_policy")
+        .await?
+        .json()?;
+    assert!(single_key_policy);
+
+    // The vault records Alice's key once it's added
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let alice_worker = get_worker_info(&solver_registry, &alice)
+        .await?
+        .expect("Alice should be registered as a worker");
+    let authorized_keys: Vec<PublicKey> = sandbox
+        .view(&pool_account_id, "get_authorized_keys")
+        .await?
+        .json()?;
+    assert_eq!(authorized_keys, vec![alice_worker.public_key.clone()]);
+
+    // Only the registry can manage the vault keys
+    let result = alice
+        .call(&pool_account_id, "add_public_key")
+        .args_json(json!({
+            "public_key": alice_worker.public_key,
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Only the registry should be able to add vault keys"
+    );
+
+    // Bob replaces Alice after her timeout, the single-key policy doesn't block the replacement
+    wait_for_worker_timeout(5).await;
+    let result = register_worker_bob(&bob, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let bob_worker = get_worker_info(&solver_registry, &bob)
+        .await?
+        .expect("Bob should be registered as a worker");
+    let authorized_keys: Vec<PublicKey> = sandbox
+        .view(&pool_account_id, "get_authorized_keys")
+        .await?
+        .json()?;
+    assert_eq!(authorized_keys, vec![bob_worker.public_key.clone()]);
+
+    // The vault state matches the keys on NEAR Intents
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert_eq!(public_keys.len(), 1);
+    assert!(public_keys.contains(&bob_worker.public_key));
+
+    println!("Test passed: The vault tracks its authorized keys");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reconcile_pool_keys() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for pool key reconciliation...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, _bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool and register Alice as its worker
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    approve_compose_hash(&owner, &solver_registry).await?;
+    let result = register_worker_alice(&alice, &solver_registry, 0).await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+
+    // Only the pool operator can reconcile the pool keys
+    let result = alice
+        .call(solver_registry.id(), "reconcile_pool_keys")
+        .args_json(json!({"pool_id": 0}))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Non-operator should not be able to reconcile the pool keys"
+    );
+
+    // Nothing to reconcile when the keys match the registered worker
+    let result = owner
+        .call(solver_registry.id(), "reconcile_pool_keys")
+        .args_json(json!({"pool_id": 0}))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let report = result
+        .logs()
+        .iter()
+        .find(|log| log.contains("pool_keys_reconciled"))
+        .expect("Reconciliation should emit a report")
+        .to_string();
+    assert!(report.contains(r#""removed_keys":[]"#));
+    assert!(report.contains(r#""removed_worker_id":null"#));
+
+    let worker = get_worker_info(&solver_registry, &alice).await?;
+    let worker = worker.expect("Alice should still be registered");
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert_eq!(public_keys.len(), 1);
+    assert!(public_keys.contains(&worker.public_key));
+
+    // A key added without the registry is removed
+    let pool_account = get_pool_account(&sandbox, &solver_registry, 0).await?;
+    let orphan_key: PublicKey = SecretKey::from_random(KeyType::ED25519)
+        .public_key()
+        .to_string()
+        .parse()
+        .unwrap();
+    let result = pool_account
+        .call(mock_intents.id(), "add_public_key")
+        .args_json(json!({"public_key": orphan_key}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = reconcile_pool_keys(&owner, &solver_registry, 0).await?;
+    let report = find_reconciliation_report(&result);
+    assert_eq!(report["removed_keys"], json!([orphan_key]));
+    assert_eq!(report["removed_worker_id"], json!(null));
+    let public_keys = get_pool_public_keys(&mock_intents, &pool_account_id).await?;
+    assert_eq!(public_keys.len(), 1);
+    assert!(public_keys.contains(&worker.public_key));
+    assert!(get_worker_info(&solver_registry, &alice).await?.is_some());
+
+    // The worker is removed once its key is no longer on NEAR Intents
+    let result = pool_account
+        .call(mock_intents.id(), "remove_public_key")
+        .args_json(json!({"public_key": worker.public_key}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    let result = reconcile_pool_keys(&owner, &solver_registry, 0).await?;
+    let report = find_reconciliation_report(&result);
+    assert_eq!(report["removed_keys"], json!([]));
+    assert_eq!(report["removed_worker_id"], json!(alice.id()));
+    assert!(get_worker_info(&solver_registry, &alice).await?.is_none());
+    let pool = get_pool_info(&solver_registry, 0).await?;
+    assert!(pool.worker_id.is_none());
+
+    println!("Test passed: Pool keys are reconciled with the registered worker");
+
+    Ok(())
+}
+
+// Helper function to reconcile the keys of a pool as its operator
+async fn reconcile_pool_keys(
+    operator: &near_workspaces::Account,
+    solver_registry: &near_workspaces::Contract,
+    pool_id: u32,
+) -> Result<near_workspaces::result::ExecutionFinalResult, Box<dyn std::error::Error>> {
+    let result = operator
+        .call(solver_registry.id(), "reconcile_pool_keys")
+        .args_json(json!({"pool_id": pool_id}))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(300))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    Ok(result)
+}
+
+// Helper function to find the data of the `PoolKeysReconciled` event
+fn find_reconciliation_report(
+    result: &near_workspaces::result::ExecutionFinalResult,
+) -> serde_json::Value {
+    result
+        .logs()
+        .iter()
+        .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+        .map(|log| serde_json::from_str::<serde_json::Value>(log).unwrap())
+        .find(|event| event["event"] == "pool_keys_reconciled")
+        .expect("Reconciliation should emit a report")["data"][0]
+        .clone()
+}
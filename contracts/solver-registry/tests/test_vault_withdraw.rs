@@ -0,0 +1,129 @@
+use near_gas::NearGas;
+use near_sdk::NearToken;
+use serde_json::json;
+
+mod common;
+
+use common::utils::*;
+
+#[tokio::test]
+async fn test_vault_withdraw_and_transfer() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Starting test for vault withdrawals and transfers...");
+    let sandbox = near_workspaces::sandbox().await?;
+
+    // Setup test environment
+    let (wnear, usdc, owner, alice, bob, mock_intents, solver_registry) =
+        setup_test_environment(&sandbox, 10 * 60 * 1000).await?;
+
+    // Create a liquidity pool, seeded with 1 NEAR and 2 USDC
+    create_liquidity_pool(&solver_registry, &wnear, &usdc).await?;
+    seed_pool(
+        &solver_registry,
+        &owner,
+        0,
+        &wnear,
+        &usdc,
+        1_000_000_000_000_000_000_000_000,
+        2_000_000,
+    )
+    .await?;
+    let pool_account_id = get_pool_account_id(&solver_registry, 0);
+
+    // Only the registry can withdraw from the vault
+    let result = alice
+        .call(&pool_account_id, "ft_withdraw")
+        .args_json(json!({
+            "token": usdc.id(),
+            "receiver_id": alice.id(),
+            "amount": "500000",
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Only the registry should be able to withdraw from the vault"
+    );
+
+    // The registry withdraws 0.5 USDC to Alice
+    let alice_balance = ft_balance_of(&usdc, alice.id()).await?;
+    let result = solver_registry
+        .as_account()
+        .call(&pool_account_id, "ft_withdraw")
+        .args_json(json!({
+            "token": usdc.id(),
+            "receiver_id": alice.id(),
+            "amount": "500000",
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert_eq!(
+        ft_balance_of(&usdc, alice.id()).await?,
+        alice_balance + 500_000
+    );
+    assert_eq!(
+        mt_balance_of(&mock_intents, &pool_account_id, &usdc).await?,
+        1_500_000
+    );
+
+    // The registry transfers 0.5 USDC to Bob within NEAR Intents
+    let result = solver_registry
+        .as_account()
+        .call(&pool_account_id, "transfer")
+        .args_json(json!({
+            "receiver_id": bob.id(),
+            "token_ids": [format!("nep141:{}", usdc.id())],
+            "amounts": ["500000"],
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    assert!(
+        result.is_success(),
+        "{:#?}",
+        result.into_result().unwrap_err()
+    );
+    assert_eq!(
+        mt_balance_of(&mock_intents, bob.id(), &usdc).await?,
+        500_000
+    );
+    assert_eq!(
+        mt_balance_of(&mock_intents, &pool_account_id, &usdc).await?,
+        1_000_000
+    );
+
+    // Transfers can't exceed the vault balance
+    let result = solver_registry
+        .as_account()
+        .call(&pool_account_id, "transfer")
+        .args_json(json!({
+            "receiver_id": bob.id(),
+            "token_ids": [format!("nep141:{}", usdc.id())],
+            "amounts": ["2000000"],
+        }))
+        .deposit(NearToken::from_yoctonear(1))
+        .gas(NearGas::from_tgas(100))
+        .transact()
+        .await?;
+    assert!(
+        !result.is_success(),
+        "Transfers beyond the vault balance should fail"
+    );
+    assert_eq!(
+        mt_balance_of(&mock_intents, &pool_account_id, &usdc).await?,
+        1_000_000
+    );
+
+    println!("Test passed: The registry withdraws and transfers the vault balances");
+
+    Ok(())
+}